@@ -0,0 +1,81 @@
+//! Benchmarks of mining, Merkle root construction, and header hashing.
+use blockchain_sandbox::blockchain::{DEFAULT_MAX_MINING_ITERATIONS, ZERO_HASH};
+use blockchain_sandbox::{BlockHeader, Chain, HashAlgo, Transaction};
+use chrono::{DateTime, Utc};
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::sync::atomic::AtomicBool;
+
+fn header(difficulty: u32) -> BlockHeader {
+    BlockHeader::new(
+        DateTime::<Utc>::UNIX_EPOCH,
+        ZERO_HASH.to_string(),
+        ZERO_HASH.to_string(),
+        difficulty,
+    )
+}
+
+fn proof_of_work(c: &mut Criterion) {
+    let mut group = c.benchmark_group("proof_of_work");
+    for difficulty in 1..=3 {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(difficulty),
+            &difficulty,
+            |b, &difficulty| {
+                b.iter_batched(
+                    || header(difficulty),
+                    |mut header| {
+                        Chain::proof_of_work(
+                            &mut header,
+                            DEFAULT_MAX_MINING_ITERATIONS,
+                            HashAlgo::Sha256,
+                            1,
+                            &AtomicBool::new(false),
+                            false,
+                        )
+                        .unwrap();
+                        header
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn get_merkle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_merkle");
+    for count in [1, 10, 100] {
+        let transactions: Vec<_> = (0..count)
+            .map(|i| Transaction::new("Alice".to_string(), "Bob".to_string(), i + 1))
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &transactions,
+            |b, transactions| {
+                b.iter(|| Chain::get_merkle(black_box(transactions), HashAlgo::Sha256));
+            },
+        );
+    }
+    group.finish();
+}
+
+fn hash_header(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_header");
+    let header = header(1);
+    for algo in [
+        HashAlgo::Sha256,
+        HashAlgo::Sha256d,
+        HashAlgo::Sha3_256,
+        HashAlgo::Blake3,
+    ] {
+        group.bench_with_input(BenchmarkId::from_parameter(algo), &header, |b, header| {
+            b.iter(|| algo.hash(black_box(header)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, proof_of_work, get_merkle, hash_header);
+criterion_main!(benches);
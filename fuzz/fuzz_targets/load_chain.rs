@@ -0,0 +1,17 @@
+//! Feeds arbitrary bytes to the JSON and binary chain loaders.
+//!
+//! Malformed input must come back as an error; a panic is a bug. Whatever the
+//! loaders accept must also pass validation.
+#![no_main]
+
+use blockchain_sandbox::Chain;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for chain in [Chain::from_json(data), Chain::from_bin(data)]
+        .into_iter()
+        .flatten()
+    {
+        assert!(chain.is_valid());
+    }
+});
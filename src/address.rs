@@ -0,0 +1,85 @@
+//! Checksummed addresses derived from Ed25519 public keys.
+//!
+//! An address is the base58 encoding of the 32-byte public key followed by the
+//! first four bytes of its SHA-256 digest. Mistyping a character changes the
+//! decoded key or checksum, so the address no longer checks out.
+use sha2::{Digest, Sha256};
+
+/// Number of checksum bytes appended to the public key.
+const CHECKSUM_LEN: usize = 4;
+
+/// Returns the checksum of a public key.
+fn checksum(public_key: &[u8; 32]) -> [u8; CHECKSUM_LEN] {
+    let mut checksum = [0; CHECKSUM_LEN];
+    checksum.copy_from_slice(&Sha256::digest(public_key)[..CHECKSUM_LEN]);
+    checksum
+}
+
+/// Encodes a public key as a checksummed address.
+///
+/// # Arguments
+///
+/// * `public_key` - Ed25519 public key
+///
+/// # Returns
+///
+/// The base58 address
+pub fn from_public_key(public_key: &[u8; 32]) -> String {
+    let mut bytes = public_key.to_vec();
+    bytes.extend_from_slice(&checksum(public_key));
+    bs58::encode(bytes).into_string()
+}
+
+/// Decodes a checksummed address back into its public key.
+///
+/// # Arguments
+///
+/// * `address` - Address produced by [`from_public_key`]
+///
+/// # Returns
+///
+/// The public key, or None if the address is not base58, has the wrong
+/// length, or its checksum does not match
+pub fn to_public_key(address: &str) -> Option<[u8; 32]> {
+    let bytes = bs58::decode(address).into_vec().ok()?;
+    let (key, sum) = bytes.split_at_checked(32)?;
+    let key = <[u8; 32]>::try_from(key).ok()?;
+    (sum == checksum(&key)).then_some(key)
+}
+
+/// Checks whether an address is well formed and its checksum matches.
+pub fn is_valid(address: &str) -> bool {
+    to_public_key(address).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_address_round_trips() {
+        let key = [7; 32];
+        let address = from_public_key(&key);
+
+        assert!(is_valid(&address));
+        assert_eq!(to_public_key(&address), Some(key));
+    }
+
+    #[test]
+    fn mutated_character_is_rejected() {
+        let address = from_public_key(&[7; 32]);
+        for i in 0..address.len() {
+            let mut mutated = address.clone().into_bytes();
+            mutated[i] = if mutated[i] == b'2' { b'3' } else { b'2' };
+            let mutated = String::from_utf8(mutated).unwrap();
+            assert!(!is_valid(&mutated), "{mutated}");
+        }
+    }
+
+    #[test]
+    fn malformed_addresses_are_rejected() {
+        for address in ["", "Bob", "0OIl", &from_public_key(&[7; 32])[1..]] {
+            assert!(!is_valid(address), "{address:?}");
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Integer amounts and their conversion to and from decimal coins.
+//!
+//! Amounts, fees, and rewards are counted in base units, the smallest amount
+//! that can be transferred, so sums and balances are exact. One coin is
+//! [`COIN`] base units.
+
+/// Number of base units in one coin.
+pub const COIN: u64 = 100_000_000;
+
+/// Number of decimal places of a coin amount.
+const DECIMALS: usize = 8;
+
+/// Formats a number of base units as decimal coins.
+///
+/// Trailing zeros of the fractional part are dropped, and so is the decimal
+/// point when the amount is a whole number of coins.
+///
+/// # Arguments
+///
+/// * `units` - Amount in base units
+///
+/// # Returns
+///
+/// The amount in coins, for example `"10"` or `"0.25"`
+pub fn format_amount(units: u64) -> String {
+    let whole = units / COIN;
+    let fraction = units % COIN;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{fraction:0DECIMALS$}");
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// Parses decimal coins into base units.
+///
+/// # Arguments
+///
+/// * `coins` - Amount in coins with at most eight decimal places, for example
+///   `"10"` or `"0.25"`
+///
+/// # Returns
+///
+/// The amount in base units, or None if `coins` is not a non-negative decimal
+/// number, has more than eight decimal places, or does not fit in a `u64`
+pub fn parse_amount(coins: &str) -> Option<u64> {
+    let coins = coins.trim();
+    let (whole, fraction) = coins.split_once('.').unwrap_or((coins, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > DECIMALS
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<DECIMALS$}").parse().ok()?
+    };
+    whole.checked_mul(COIN)?.checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_whole_and_fractional_coins() {
+        assert_eq!(format_amount(0), "0");
+        assert_eq!(format_amount(10 * COIN), "10");
+        assert_eq!(format_amount(COIN / 4), "0.25");
+        assert_eq!(format_amount(COIN + 1), "1.00000001");
+    }
+
+    #[test]
+    fn parses_decimal_coins() {
+        assert_eq!(parse_amount("10"), Some(10 * COIN));
+        assert_eq!(parse_amount("0.25"), Some(COIN / 4));
+        assert_eq!(parse_amount(".5"), Some(COIN / 2));
+        assert_eq!(parse_amount("1.00000001"), Some(COIN + 1));
+        assert_eq!(parse_amount(" 3 "), Some(3 * COIN));
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for coins in ["", ".", "-1", "1e3", "abc", "1.000000001", "1.2.3"] {
+            assert_eq!(parse_amount(coins), None, "{coins:?}");
+        }
+        assert_eq!(parse_amount(&u64::MAX.to_string()), None);
+    }
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        for units in [1, COIN - 1, 21_000_000 * COIN, u64::MAX] {
+            assert_eq!(parse_amount(&format_amount(units)), Some(units));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! An append-only trail of the operations performed on a chain, for debugging.
+//!
+//! Chains built with [`ChainConfig::audit`](crate::ChainConfig::audit), or after
+//! [`Chain::set_audit`](crate::Chain::set_audit), record an [`AuditEntry`] for
+//! each accepted transaction, appended block, and change of difficulty or
+//! reward. The log is separate from the chain and is not saved with it;
+//! [`Chain::export_audit_log`](crate::Chain::export_audit_log) writes it as
+//! JSON.
+use crate::blockchain::Transaction;
+use crate::hash::HashAlgo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An operation recorded in a chain's audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Time the chain's clock read when the operation happened
+    pub timestamp: DateTime<Utc>,
+    /// What happened
+    pub event: AuditEvent,
+}
+
+/// The kinds of operations an audit log records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A transaction entered the pending pool
+    TransactionAdded {
+        /// Id of the transaction, see [`Transaction::txid`]
+        txid: String,
+        /// Address of the sender
+        sender: String,
+        /// Address of the receiver
+        receiver: String,
+        /// Amount transferred, in base units
+        amount: u64,
+        /// Fee paid to the miner, in base units
+        fee: u64,
+    },
+    /// A block was appended to the chain, whether mined by it or submitted
+    BlockAdded {
+        /// Index of the block
+        index: u64,
+        /// Hash of the block header
+        hash: String,
+        /// Number of transactions in the block, the coinbase included
+        transactions: usize,
+    },
+    /// The mining difficulty changed, by an update or a retarget
+    DifficultyChanged {
+        /// Previous difficulty
+        from: u32,
+        /// New difficulty
+        to: u32,
+    },
+    /// The base mining reward changed
+    RewardChanged {
+        /// Previous reward, in base units
+        from: u64,
+        /// New reward, in base units
+        to: u64,
+    },
+}
+
+impl AuditEvent {
+    /// Describes a transaction added to the pending pool of a chain hashing
+    /// with `algo`.
+    pub(crate) fn transaction_added(transaction: &Transaction, algo: HashAlgo) -> Self {
+        Self::TransactionAdded {
+            txid: transaction.txid(algo),
+            sender: transaction.sender.clone(),
+            receiver: transaction.receiver.clone(),
+            amount: transaction.amount,
+            fee: transaction.fee,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::COIN;
+    use crate::blockchain::Chain;
+    use crate::config::ChainConfig;
+
+    #[test]
+    fn audit_log_records_operations_in_order() {
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).audit(true)).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain.update_difficulty(2).unwrap();
+        chain.update_reward(25 * COIN);
+        chain.update_reward(25 * COIN);
+
+        let events: Vec<&AuditEvent> = chain.audit_log().iter().map(|e| &e.event).collect();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(events[0], AuditEvent::BlockAdded { index: 0, .. }));
+        assert!(matches!(
+            events[1],
+            AuditEvent::TransactionAdded { receiver, amount, .. } if receiver == "Bob" && *amount == COIN
+        ));
+        assert!(matches!(
+            events[2],
+            AuditEvent::BlockAdded { index: 1, hash, transactions: 2 }
+                if hash == chain.latest().unwrap().get_hash()
+        ));
+        assert_eq!(events[3], &AuditEvent::DifficultyChanged { from: 1, to: 2 });
+        assert_eq!(
+            events[4],
+            &AuditEvent::RewardChanged {
+                from: 50 * COIN,
+                to: 25 * COIN
+            }
+        );
+        assert!(
+            chain
+                .audit_log()
+                .windows(2)
+                .all(|pair| pair[0].timestamp <= pair[1].timestamp)
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "blockchain-sandbox-{}-audit.json",
+            std::process::id()
+        ));
+        chain.export_audit_log(&path).unwrap();
+        let exported: Vec<AuditEntry> =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(exported, chain.audit_log());
+    }
+
+    #[test]
+    fn audit_log_is_off_by_default() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        assert!(chain.audit_log().is_empty());
+
+        chain.set_audit(true);
+        chain.update_reward(COIN);
+        assert_eq!(chain.audit_log().len(), 1);
+        chain.set_audit(false);
+        assert!(chain.audit_log().is_empty());
+    }
+}
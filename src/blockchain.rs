@@ -1,29 +1,498 @@
 //! A blockchain implementation with proof-of-work mining.
+use crate::address;
+use crate::amount::format_amount;
+use crate::audit::{AuditEntry, AuditEvent};
+use crate::clock::{Clock, SystemClock};
+use crate::config::ChainConfig;
+use crate::consensus::{Consensus, ProofOfWork};
+use crate::encode::Encode;
+use crate::error::BlockchainError;
+use crate::hash::HashAlgo;
+use crate::script;
+use crate::storage::{MemoryStorage, Storage};
+use crate::target::{DifficultyUnit, Target};
+use crate::wallet::Wallet;
 ///
 /// This module contains structures and functionality for a simple blockchain,
 /// including transaction management, block creation, and proof-of-work mining.
 use chrono::{DateTime, Utc};
-use serde::Serialize;
-use sha2::{Digest, Sha256};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Hash used as the `previous_hash` of the genesis block.
+pub const ZERO_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Sender address of coinbase (mining reward) transactions, the emission source.
+pub const COINBASE_SENDER: &str = "Root";
+
+/// Default number of blocks between reward halvings.
+pub const DEFAULT_HALVING_INTERVAL: u64 = 210_000;
+
+/// Default block time targeted by difficulty retargeting.
+pub const DEFAULT_TARGET_BLOCK_TIME: Duration = Duration::from_secs(10);
+
+/// How far ahead of the chain's clock a submitted block may be stamped.
+pub const MAX_FUTURE_BLOCK_TIME: Duration = Duration::from_hours(2);
+
+/// Number of latest blocks whose timestamps make up the median time past.
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Default maximum number of pending transactions.
+pub const DEFAULT_MAX_MEMPOOL: usize = 1000;
+
+/// Default age after which a pending transaction is evicted, two weeks.
+pub const DEFAULT_MEMPOOL_TTL: Duration = Duration::from_hours(14 * 24);
+
+/// Default maximum number of pending transactions included in a block.
+pub const DEFAULT_MAX_BLOCK_TXS: usize = 100;
+
+/// Default maximum length of a block's binary encoding, in bytes.
+pub const DEFAULT_MAX_BLOCK_BYTES: usize = 1_000_000;
+
+/// Bytes reserved for the transaction count prefix of a block growing past a
+/// single byte.
+const MAX_COUNT_PREFIX_GROWTH: usize = 8;
+
+/// Default maximum number of hashes tried while mining a single block.
+pub const DEFAULT_MAX_MINING_ITERATIONS: u64 = 1 << 32;
+
+/// Returns the default number of mining threads, one per available CPU.
+fn default_mining_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+}
+
+/// Checks that a reward split names valid addresses, when `check_addresses`
+/// is set, and gives away at most the whole reward.
+fn check_reward_split(
+    split: &[(String, u32)],
+    check_addresses: bool,
+) -> Result<(), BlockchainError> {
+    if let Some((address, _)) = split
+        .iter()
+        .find(|(address, _)| check_addresses && !address::is_valid(address))
+    {
+        return Err(BlockchainError::InvalidAddress(address.clone()));
+    }
+    let shares: u64 = split.iter().map(|&(_, share)| u64::from(share)).sum();
+    if shares > 100 {
+        return Err(BlockchainError::InvalidRewardSplit(shares));
+    }
+    Ok(())
+}
+
+/// Number of hashes a mining worker tries between progress bar updates.
+const PROGRESS_TICK: u64 = 1024;
+
+/// Highest supported mining difficulty (length of a hex-encoded SHA-256 hash).
+pub const MAX_DIFFICULTY: u32 = 64;
+
+/// Maximum length of a transaction memo, in bytes.
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Version of the chain file format written by [`Chain::save_to_file`] and
+/// [`Chain::save_bin`].
+///
+/// JSON files of earlier versions are migrated when loaded, while binary files
+/// must be of this version; loading any other file fails with
+/// [`BlockchainError::UnsupportedVersion`]. JSON files saved before the version
+/// was recorded are read as version 1.
+pub const FORMAT_VERSION: u32 = 8;
+
+/// Tag at the start of every binary chain file.
+const BIN_MAGIC: &[u8; 4] = b"BCSB";
+
+/// Maximum number of bytes [`Chain::from_bin`] decodes, so a corrupt length
+/// prefix cannot make it allocate without bound.
+const MAX_BIN_LEN: usize = 256 * 1024 * 1024;
+
+/// Reference to an output of a committed transaction.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OutPoint {
+    /// Id of the transaction holding the output, see [`Transaction::txid`]
+    pub txid: String,
+    /// Position of the output among the transaction's outputs
+    pub index: u32,
+}
+
+impl std::fmt::Display for OutPoint {
+    /// Formats the reference as `txid:index`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.txid, self.index)
+    }
+}
+
+impl Encode for OutPoint {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.txid.encode(out);
+        self.index.encode(out);
+    }
+}
+
+/// An amount paid to an address by a transaction, spendable once as an input
+/// of a later one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOut {
+    /// Address the output belongs to
+    pub address: String,
+    /// Amount of the output, in base units
+    pub amount: u64,
+    /// Script every transaction spending the output must satisfy, if any, see
+    /// [`script`](crate::script)
+    #[serde(default)]
+    pub script: Option<Vec<u8>>,
+}
+
+impl Encode for TxOut {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.address.encode(out);
+        self.amount.encode(out);
+        // Outputs without a script keep their earlier encoding.
+        if self.script.is_some() {
+            self.script.encode(out);
+        }
+    }
+}
+
+/// Fields of a [`TxOut`] covered by the signature of the transaction creating
+/// it, leaving out a missing script so that outputs without one keep their
+/// earlier payload.
+#[derive(Serialize)]
+struct OutputPayload<'a> {
+    /// Address the output belongs to
+    address: &'a str,
+    /// Amount of the output, in base units
+    amount: u64,
+    /// Script locking the output, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    script: Option<&'a [u8]>,
+}
+
+/// Unspent outputs of a chain, by the reference that spends them.
+pub type UtxoSet = BTreeMap<OutPoint, TxOut>;
+
+/// Serializes a [`UtxoSet`] as a list of entries, since JSON map keys must be
+/// strings.
+mod utxo_entries {
+    use super::{OutPoint, TxOut, UtxoSet};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        unspent: &UtxoSet,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(unspent)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<UtxoSet, D::Error> {
+        Ok(Vec::<(OutPoint, TxOut)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
 
 /// Represents a transaction between two parties.
 ///
 /// A transaction records the transfer of assets from a sender to a receiver.
-#[derive(Debug, Clone, Serialize)]
-pub(crate) struct Transaction {
+///
+/// A transaction built with [`Transaction::spend`] also lists the outputs it
+/// spends and creates, as in a UTXO model: its receiver and amount are then the
+/// address of the first output and the total of all outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
     /// Address of the sender
     pub sender: String,
     /// Address of the receiver
     pub receiver: String,
-    /// Amount transferred
-    pub amount: f32,
+    /// Amount transferred, in base units
+    pub amount: u64,
+    /// Fee paid by the sender to the miner of the including block, in base units
+    pub fee: u64,
+    /// Ed25519 signature over the transaction by the sender's key, if signed
+    pub signature: Option<Vec<u8>>,
+    /// Earliest block timestamp the transaction can be included at, if locked
+    #[serde(default)]
+    pub lock_until: Option<DateTime<Utc>>,
+    /// Free-form annotation of at most [`MAX_MEMO_LEN`] bytes, if any
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Outputs of earlier transactions spent by this one, all owned by the
+    /// sender
+    #[serde(default)]
+    pub inputs: Vec<OutPoint>,
+    /// Outputs created by this transaction
+    #[serde(default)]
+    pub outputs: Vec<TxOut>,
+    /// Time the transaction was created, if stamped; pending transactions
+    /// older than the chain's mempool TTL are evicted. It is not covered by the
+    /// id or signature, so the same transfer stamped twice is a duplicate
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl Transaction {
+    /// Creates a new unsigned transaction without a fee.
+    pub const fn new(sender: String, receiver: String, amount: u64) -> Self {
+        Self {
+            sender,
+            receiver,
+            amount,
+            fee: 0,
+            signature: None,
+            lock_until: None,
+            memo: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            timestamp: None,
+        }
+    }
+
+    /// Creates an unsigned transaction spending outputs of earlier transactions.
+    ///
+    /// The inputs must add up to the outputs plus the fee, so any remainder
+    /// should be paid back to the sender as a change output. Without inputs,
+    /// the outputs are funded from the sender's balance like a plain transfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Address owning every input
+    /// * `inputs` - Unspent outputs to spend, see [`Chain::unspent_outputs`]
+    /// * `outputs` - Outputs to create
+    pub fn spend(sender: String, inputs: Vec<OutPoint>, outputs: Vec<TxOut>) -> Self {
+        let receiver = outputs
+            .first()
+            .map(|output| output.address.clone())
+            .unwrap_or_default();
+        let amount = outputs
+            .iter()
+            .fold(0, |total: u64, output| total.saturating_add(output.amount));
+        Self {
+            inputs,
+            outputs,
+            ..Self::new(sender, receiver, amount)
+        }
+    }
+
+    /// Sets the fee paid to the miner.
+    #[must_use]
+    pub const fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Locks the transaction out of blocks stamped before `lock_until`.
+    #[must_use]
+    pub const fn with_lock_until(mut self, lock_until: DateTime<Utc>) -> Self {
+        self.lock_until = Some(lock_until);
+        self
+    }
+
+    /// Stamps the transaction with its creation time.
+    #[must_use]
+    pub const fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Attaches a memo to the transaction.
+    #[must_use]
+    pub fn with_memo(mut self, memo: String) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    /// Returns `true` if the transaction can be included in a block stamped
+    /// `timestamp`.
+    pub fn is_unlocked_at(&self, timestamp: DateTime<Utc>) -> bool {
+        self.lock_until
+            .is_none_or(|lock_until| lock_until <= timestamp)
+    }
+
+    /// Returns `true` if the transaction is a mining reward, sent from
+    /// [`COINBASE_SENDER`].
+    pub fn is_coinbase(&self) -> bool {
+        self.sender == COINBASE_SENDER
+    }
+
+    /// Returns the addresses credited by the transaction and their amounts: its
+    /// outputs, or the receiver if it has none.
+    ///
+    /// Outputs locked by a script are left out; their coins reach an account
+    /// only through the transaction spending them.
+    pub fn credits(&self) -> Vec<(&str, u64)> {
+        if self.outputs.is_empty() {
+            vec![(self.receiver.as_str(), self.amount)]
+        } else {
+            self.outputs
+                .iter()
+                .filter(|output| output.script.is_none())
+                .map(|output| (output.address.as_str(), output.amount))
+                .collect()
+        }
+    }
+
+    /// Returns the amount debited from the sender, the amount plus the fee.
+    pub const fn debit(&self) -> u64 {
+        self.amount.saturating_add(self.fee)
+    }
+
+    /// Returns `true` if the amount and receiver match the outputs, if any,
+    /// as set by [`Transaction::spend`].
+    fn outputs_match(&self) -> bool {
+        let total: u128 = self.outputs.iter().map(|o| u128::from(o.amount)).sum();
+        self.outputs.is_empty()
+            || (total == u128::from(self.amount) && self.outputs[0].address == self.receiver)
+    }
+
+    /// Returns `true` if the transaction is another version of `other`: both
+    /// come from the same sender and spend a common output or, without inputs,
+    /// credit the same amounts to the same addresses.
+    fn conflicts_with(&self, other: &Self) -> bool {
+        self.sender == other.sender
+            && if self.inputs.is_empty() && other.inputs.is_empty() {
+                self.credits() == other.credits()
+            } else {
+                self.inputs.iter().any(|input| other.inputs.contains(input))
+            }
+    }
+
+    /// Returns the bytes covered by the transaction signature.
+    pub fn signing_payload(&self) -> Result<Vec<u8>, BlockchainError> {
+        let fields = (
+            &self.sender,
+            &self.receiver,
+            self.amount,
+            self.fee,
+            self.lock_until,
+            &self.memo,
+        );
+        // Transfers without inputs or outputs keep their earlier payload.
+        Ok(if self.inputs.is_empty() && self.outputs.is_empty() {
+            serde_json::to_vec(&fields)?
+        } else {
+            let outputs: Vec<OutputPayload<'_>> = self
+                .outputs
+                .iter()
+                .map(|output| OutputPayload {
+                    address: &output.address,
+                    amount: output.amount,
+                    script: output.script.as_deref(),
+                })
+                .collect();
+            serde_json::to_vec(&(fields, &self.inputs, outputs))?
+        })
+    }
+
+    /// Computes the transaction id, the hash of the transaction's canonical
+    /// encoding.
+    ///
+    /// Transactions with identical fields, signature included, share an id. It
+    /// equals the Merkle leaf of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `algo` - Hash function of the chain holding the transaction
+    ///
+    /// # Returns
+    ///
+    /// Hexadecimal transaction id
+    pub fn txid(&self, algo: HashAlgo) -> String {
+        algo.hash(self)
+    }
+
+    /// Signs the transaction with the sender's private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - Wallet whose address is the sender of this transaction
+    pub fn sign(&mut self, wallet: &Wallet) -> Result<(), BlockchainError> {
+        self.signature = Some(wallet.sign(self)?);
+        Ok(())
+    }
+
+    /// Verifies the signature against the sender's public key.
+    ///
+    /// The sender address is interpreted as a hex-encoded Ed25519 public key or
+    /// as a checksummed [`address`](crate::address).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the transaction carries a valid signature from the sender
+    pub fn verify(&self) -> bool {
+        Chain::hex_to_bytes(&self.sender)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .or_else(|| address::to_public_key(&self.sender))
+            .is_some_and(|key| self.verify_with_key(&key))
+    }
+
+    /// Verifies the signature against an Ed25519 public key, whatever the
+    /// sender.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the transaction carries a valid signature by `public_key`
+    pub fn verify_with_key(&self, public_key: &[u8; 32]) -> bool {
+        let Some(signature) = &self.signature else {
+            return false;
+        };
+        let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+            return false;
+        };
+        let (Ok(signature), Ok(payload)) =
+            (Signature::from_slice(signature), self.signing_payload())
+        else {
+            return false;
+        };
+        key.verify(&payload, &signature).is_ok()
+    }
+}
+
+impl std::fmt::Display for Transaction {
+    /// Formats the transaction as `sender -> receiver: amount`, followed by the
+    /// fee if there is one.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {}: {}",
+            self.sender,
+            self.receiver,
+            format_amount(self.amount)
+        )?;
+        if self.fee > 0 {
+            write!(f, " (fee {})", format_amount(self.fee))?;
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Transaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.sender.encode(out);
+        self.receiver.encode(out);
+        self.amount.encode(out);
+        self.fee.encode(out);
+        self.lock_until.encode(out);
+        self.memo.encode(out);
+        self.signature.encode(out);
+        // Transfers without inputs or outputs keep their earlier id.
+        if !self.inputs.is_empty() || !self.outputs.is_empty() {
+            self.inputs.encode(out);
+            self.outputs.encode(out);
+        }
+    }
 }
 
 /// Header information for a block in the blockchain.
 ///
 /// Contains metadata and proof-of-work elements required for blockchain integrity.
-#[derive(Serialize, Debug, Clone)]
-pub(crate) struct BlockHeader {
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
     /// Time when the block was created
     timestamp: DateTime<Utc>,
     /// Counter used for proof-of-work mining
@@ -34,49 +503,309 @@ pub(crate) struct BlockHeader {
     merkle: String,
     /// Number of leading zeros required in hash (mining difficulty)
     difficulty: u32,
+    /// Whether `difficulty` counts leading zero hex digits or bits
+    #[serde(default)]
+    unit: DifficultyUnit,
+    /// Compact target the hash must not exceed, checked instead of `difficulty`
+    /// when set
+    #[serde(default)]
+    bits: Option<u32>,
 }
 
 impl BlockHeader {
+    /// Creates an unmined header with nonce 0 and a leading-zero hex digit
+    /// difficulty, ready for [`Chain::proof_of_work`].
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - Time the block is stamped with
+    /// * `previous_hash` - Hash of the previous block's header
+    /// * `merkle` - Merkle root of the block's transactions
+    /// * `difficulty` - Number of leading `'0'` hex digits required of the hash
+    pub const fn new(
+        timestamp: DateTime<Utc>,
+        previous_hash: String,
+        merkle: String,
+        difficulty: u32,
+    ) -> Self {
+        Self {
+            timestamp,
+            nonce: 0,
+            previous_hash,
+            merkle,
+            difficulty,
+            unit: DifficultyUnit::HexDigits,
+            bits: None,
+        }
+    }
+
     /// Returns the nonce value of this block header.
-    pub(crate) const fn get_nonce(&self) -> u64 {
+    pub const fn get_nonce(&self) -> u64 {
         self.nonce
     }
 
     /// Returns the hash of the previous block in the chain.
-    pub(crate) fn get_previous_hash(&self) -> String {
+    pub fn get_previous_hash(&self) -> String {
         self.previous_hash.clone()
     }
+
+    /// Returns the Merkle root of the block's transactions.
+    pub fn get_merkle(&self) -> String {
+        self.merkle.clone()
+    }
+
+    /// Returns the compact target of the header, if it has one.
+    pub const fn get_bits(&self) -> Option<u32> {
+        self.bits
+    }
+
+    /// Returns the target the header hash must meet: the compact target if set,
+    /// otherwise the one equivalent to the leading-zero difficulty in its unit.
+    ///
+    /// # Returns
+    ///
+    /// The target, or None if the compact target is not a valid encoding
+    pub fn target(&self) -> Option<Target> {
+        self.bits.map_or_else(
+            || Some(self.unit.target(self.difficulty)),
+            Target::from_compact,
+        )
+    }
+
+    /// Returns the expected number of hashes needed to mine the header, or 0 if
+    /// its compact target is invalid.
+    pub fn work(&self) -> u128 {
+        self.target().map_or(0, |target| target.work())
+    }
+}
+
+impl Encode for BlockHeader {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.timestamp.encode(out);
+        self.nonce.encode(out);
+        self.previous_hash.encode(out);
+        self.merkle.encode(out);
+        self.difficulty.encode(out);
+        self.unit.encode(out);
+        self.bits.encode(out);
+    }
 }
 
 /// A block in the blockchain containing transactions.
 ///
 /// Each block includes a header with metadata and a list of transactions.
-#[derive(Serialize, Debug, Clone)]
-pub(crate) struct Block {
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Block {
     /// Metadata and proof-of-work information
     header: BlockHeader,
+    /// Height of this block in the chain (genesis = 0)
+    index: u64,
     /// Number of transactions in this block
     count: u32,
     /// List of transactions included in this block
     transactions: Vec<Transaction>,
+    /// Hash of the header, stored once the block is mined
+    #[serde(default)]
+    hash: String,
+    /// Whether [`Chain::prune`] dropped the transactions, keeping only the header
+    #[serde(default)]
+    pruned: bool,
 }
 
 impl Block {
+    /// Returns the height of this block in the chain.
+    pub const fn get_index(&self) -> u64 {
+        self.index
+    }
+
     /// Returns a reference to the block header.
-    pub(crate) const fn get_header(&self) -> &BlockHeader {
+    pub const fn get_header(&self) -> &BlockHeader {
         &self.header
     }
 
-    /// Returns a reference to the transactions in this block.
-    pub(crate) const fn get_transactions(&self) -> &Vec<Transaction> {
+    /// Returns a reference to the transactions in this block, which are empty
+    /// once it is pruned.
+    pub const fn get_transactions(&self) -> &Vec<Transaction> {
         &self.transactions
     }
+
+    /// Returns the number of transactions the block was mined with, which
+    /// [`Chain::verify_block`] checks against its transactions, and which is
+    /// kept once it is pruned.
+    pub const fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns `true` if [`Chain::prune`] dropped the transactions of this block.
+    pub const fn is_pruned(&self) -> bool {
+        self.pruned
+    }
+
+    /// Returns the stored hash of the block header.
+    pub fn get_hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Returns the length of the block's binary encoding, as in
+    /// [`Chain::save_bin`], which the chain's block size limit caps.
+    pub fn size(&self) -> usize {
+        encoded_len(self)
+    }
+}
+
+/// Returns the length of a value's binary encoding.
+fn encoded_len(value: &impl Serialize) -> usize {
+    bincode::serde::encode_to_vec(value, bincode::config::standard())
+        .map_or(usize::MAX, |bytes| bytes.len())
+}
+
+/// Number of leading hash characters shown by the [`Block`] summary.
+const SHORT_HASH_LEN: usize = 12;
+
+impl std::fmt::Display for Block {
+    /// Formats a one-line summary: index, shortened hash, transaction count,
+    /// and nonce, marking pruned blocks.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} {} ({} txs, nonce {}{})",
+            self.index,
+            self.hash.get(..SHORT_HASH_LEN).unwrap_or(&self.hash),
+            self.count,
+            self.header.nonce,
+            if self.pruned { ", pruned" } else { "" }
+        )
+    }
+}
+
+impl Encode for Block {
+    /// Encodes every field except the stored hash, which is derived from the
+    /// header.
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.header.encode(out);
+        self.index.encode(out);
+        self.count.encode(out);
+        self.transactions.encode(out);
+    }
+}
+
+/// An inconsistency in a block, found by [`Chain::find_tampering`],
+/// [`Chain::submit_block`], or [`Chain::replay`].
+///
+/// Each variant carries the index of the offending block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tampering {
+    /// The block's stored index does not match its position in the chain
+    IndexMismatch(u64),
+    /// The block's stored transaction count does not match its transactions
+    CountMismatch(u64),
+    /// The block's `previous_hash` does not match the recomputed hash of the
+    /// prior block's header
+    BrokenLink(u64),
+    /// The block's timestamp is earlier than the prior block's
+    TimestampOutOfOrder(u64),
+    /// The block's Merkle root does not match its transactions
+    MerkleMismatch(u64),
+    /// The block's first transaction is not a coinbase, or a later one is
+    MisplacedCoinbase(u64),
+    /// The block's coinbase does not pay the block subsidy plus the fees of
+    /// its transactions
+    WrongCoinbaseAmount(u64),
+    /// The block includes a transaction locked until after its timestamp
+    LockedTransaction(u64),
+    /// The block's timestamp is not after the median time past of the chain
+    TimeTooOld(u64),
+    /// The block's timestamp is too far ahead of the chain's clock
+    TimeTooNew(u64),
+    /// The block's hash differs from the one checkpointed at its height
+    CheckpointMismatch(u64),
+    /// The block's stored hash does not match the recomputed hash of its header
+    HashMismatch(u64),
+    /// The block's difficulty is not the one required at its height, or its
+    /// header hash does not meet it
+    WrongDifficulty(u64),
+    /// The block's coinbase does not pay the producer chosen by the chain's
+    /// [`Consensus`]
+    WrongProducer(u64),
+    /// The block's binary encoding is longer than the chain's block size limit
+    TooLarge(u64),
+    /// The block spends an output that is missing or already spent, or a
+    /// transaction whose inputs do not balance its outputs
+    InvalidSpend(u64),
+    /// The block includes a transaction sending more than its sender's
+    /// balance, see [`Chain::replay`]
+    Overdraft(u64),
+}
+
+impl Tampering {
+    /// Returns the index of the offending block.
+    pub const fn index(&self) -> u64 {
+        match *self {
+            Self::IndexMismatch(index)
+            | Self::CountMismatch(index)
+            | Self::BrokenLink(index)
+            | Self::TimestampOutOfOrder(index)
+            | Self::MerkleMismatch(index)
+            | Self::MisplacedCoinbase(index)
+            | Self::WrongCoinbaseAmount(index)
+            | Self::LockedTransaction(index)
+            | Self::TimeTooOld(index)
+            | Self::TimeTooNew(index)
+            | Self::CheckpointMismatch(index)
+            | Self::HashMismatch(index)
+            | Self::WrongDifficulty(index)
+            | Self::WrongProducer(index)
+            | Self::TooLarge(index)
+            | Self::InvalidSpend(index)
+            | Self::Overdraft(index) => index,
+        }
+    }
+}
+
+impl std::fmt::Display for Tampering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::IndexMismatch(_) => "index does not match its position",
+            Self::CountMismatch(_) => "transaction count does not match its transactions",
+            Self::BrokenLink(_) => "previous hash does not match the prior block",
+            Self::TimestampOutOfOrder(_) => "timestamp is earlier than the prior block",
+            Self::MerkleMismatch(_) => "Merkle root does not match its transactions",
+            Self::MisplacedCoinbase(_) => "coinbase is missing, repeated, or not first",
+            Self::WrongCoinbaseAmount(_) => "coinbase does not pay the subsidy plus fees",
+            Self::LockedTransaction(_) => "includes a transaction that is still locked",
+            Self::TimeTooOld(_) => "timestamp is not after the median time past",
+            Self::TimeTooNew(_) => "timestamp is too far in the future",
+            Self::CheckpointMismatch(_) => "hash does not match the checkpoint",
+            Self::HashMismatch(_) => "stored hash does not match its header",
+            Self::WrongDifficulty(_) => "difficulty is wrong or not met by its hash",
+            Self::WrongProducer(_) => "coinbase does not pay the chosen producer",
+            Self::TooLarge(_) => "serialized size exceeds the block size limit",
+            Self::InvalidSpend(_) => "spends an unavailable output or does not balance",
+            Self::Overdraft(_) => "sends more than its sender's balance",
+        };
+        write!(f, "block #{}: {reason}", self.index())
+    }
+}
+
+/// Callback invoked with each block appended to a chain, see [`Chain::set_on_block`].
+pub type BlockHook = Box<dyn Fn(&Block) + Send + Sync>;
+
+/// Optional [`BlockHook`] of a chain; shows only whether one is installed when
+/// debug-printed.
+#[derive(Default)]
+struct OnBlock(Option<BlockHook>);
+
+impl std::fmt::Debug for OnBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
 }
 
 /// The main blockchain data structure.
 ///
 /// Manages the chain of blocks, pending transactions, and mining operations.
-pub(crate) struct Chain {
+#[derive(Debug)]
+pub struct Chain {
     /// The sequence of validated blocks forming the blockchain
     chains: Vec<Block>,
     /// Pending transactions awaiting inclusion in the next block
@@ -85,425 +814,6527 @@ pub(crate) struct Chain {
     difficulty: u32,
     /// Address where mining rewards should be sent
     miner_address: String,
-    /// Amount awarded to the miner for successfully mining a block
-    reward: f32,
+    /// Base amount awarded to the miner for successfully mining a block, in base
+    /// units
+    reward: u64,
+    /// Number of blocks after which the block subsidy is halved
+    halving_interval: u64,
+    /// Number of blocks mined on top of a coinbase before it can be spent
+    coinbase_maturity: u64,
+    /// Addresses paid a percentage of each coinbase besides the miner
+    reward_split: Vec<(String, u32)>,
+    /// Number of blocks between difficulty adjustments (0 disables retargeting)
+    retarget_interval: u64,
+    /// Desired time between blocks used when retargeting difficulty
+    target_block_time: Duration,
+    /// Maximum number of pending transactions
+    max_mempool: usize,
+    /// Age after which a pending transaction is evicted
+    mempool_ttl: Duration,
+    /// Maximum number of pending transactions included in a block
+    max_block_txs: usize,
+    /// Maximum length of a block's binary encoding, in bytes
+    max_block_bytes: usize,
+    /// Maximum number of hashes tried while mining a single block
+    max_mining_iterations: u64,
+    /// Hash function used for block headers, transactions, and Merkle trees
+    hash_algo: HashAlgo,
+    /// Compact target of new blocks, used instead of the difficulty when set
+    target_bits: Option<u32>,
+    /// Whether the difficulty counts leading zero hex digits or bits
+    difficulty_unit: DifficultyUnit,
+    /// Whether the miner and transaction addresses must be checksummed
+    check_addresses: bool,
+    /// Address whose coins are burned, if any
+    burn_address: Option<String>,
+    /// Number of worker threads sharing the nonce search
+    mining_threads: usize,
+    /// Sum of [`BlockHeader::work`] over all blocks, used to pick between chains
+    total_work: u128,
+    /// Totals of the transactions of pruned blocks
+    pruned: PrunedTotals,
+    /// Outputs of committed transactions not yet spent
+    utxos: UtxoSet,
+    /// Trusted block hashes by height, set with [`Chain::add_checkpoint`]
+    checkpoints: BTreeMap<u64, String>,
+    /// Statistics of the blocks mined by this chain since it was created or
+    /// loaded
+    mining_stats: Vec<MiningStats>,
+    /// Whether the mining progress bar and mined blocks are not printed
+    quiet: bool,
+    /// Rule choosing the producer of each block
+    consensus: Box<dyn Consensus>,
+    /// Backend every appended block is written to
+    storage: Box<dyn Storage>,
+    /// Time source for the timestamps of newly mined blocks
+    clock: Box<dyn Clock>,
+    /// Callback invoked with each appended block
+    on_block: OnBlock,
+    /// Memo of the genesis coinbase, or empty to number it like later ones
+    genesis_message: String,
+    /// Operations recorded so far, or None if the chain keeps no audit log
+    audit: Option<Vec<AuditEntry>>,
 }
 
-impl Chain {
-    /// Creates a new blockchain with a genesis block.
-    ///
-    /// # Arguments
-    ///
-    /// * `miner_address` - Address where mining rewards will be sent
-    /// * `difficulty` - Initial mining difficulty (number of leading zeros required in hash)
-    /// * `reward` - Optional mining reward amount (defaults to 50.0 if None)
-    ///
-    /// # Returns
-    ///
-    /// A new Chain instance with a genesis block
-    pub(crate) fn new(miner_address: String, difficulty: u32, reward: Option<f32>) -> Self {
-        let reward = reward.unwrap_or(50.0); // Default reward if not provided
-        let mut chain = Self {
-            chains: Vec::new(),
-            current_transactions: Vec::new(),
-            difficulty,
-            miner_address,
-            reward,
-        };
-        chain.generate_new_block();
-        chain
-    }
+/// Totals of the transactions dropped by [`Chain::prune`], which keep balances
+/// and the supply exact once block bodies are gone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PrunedTotals {
+    /// Amounts received per address, in base units
+    received: BTreeMap<String, u64>,
+    /// Amounts plus fees sent per address, in base units
+    sent: BTreeMap<String, u64>,
+    /// Sum of the coinbase amounts, in base units
+    supply: u64,
+    /// Unspent outputs once the pruned blocks were applied
+    #[serde(default, with = "utxo_entries")]
+    utxos: UtxoSet,
+}
 
-    /// Adds a new transaction to the pending transaction pool.
-    ///
-    /// # Arguments
-    ///
-    /// * `sender` - Address of the transaction sender
-    /// * `receiver` - Address of the transaction receiver
-    /// * `amount` - Amount to transfer
-    ///
-    /// # Returns
-    ///
-    /// `true` if the transaction was successfully added
-    pub(crate) fn add_transaction(
-        &mut self,
-        sender: String,
-        receiver: String,
-        amount: f32,
-    ) -> bool {
-        let transaction = Transaction {
-            sender,
-            receiver,
-            amount,
-        };
-        self.current_transactions.push(transaction);
-        true
+impl PrunedTotals {
+    /// Adds a transaction of a pruned block to the totals.
+    fn add(&mut self, transaction: &Transaction, algo: HashAlgo) {
+        for (address, amount) in transaction.credits() {
+            let received = self.received.entry(address.to_string()).or_default();
+            *received = received.saturating_add(amount);
+        }
+        if transaction.is_coinbase() {
+            self.supply = self.supply.saturating_add(transaction.amount);
+        } else {
+            let sent = self.sent.entry(transaction.sender.clone()).or_default();
+            *sent = sent.saturating_add(charged(transaction, &self.utxos));
+        }
+        // Pruned blocks were validated, so their spends apply.
+        let _ = Chain::apply_spend(&mut self.utxos, transaction, &[], algo);
     }
+}
 
-    /// Computes the SHA-256 hash of a serializable item.
-    ///
-    /// # Arguments
-    ///
-    /// * `item` - Any serializable item to hash
-    ///
-    /// # Returns
-    ///
-    /// Hexadecimal string representation of the hash
-    pub(crate) fn hash<T: Serialize>(item: &T) -> String {
-        let update = serde_json::to_string(&item).unwrap();
-        let mut hasher = Sha256::default();
-        hasher.update(update.as_bytes());
-        let result = hasher.finalize();
-        let vec_res = result.to_vec();
+/// Amounts of the outputs locked by scripts while replaying transactions, to
+/// tell how much of each debit the spent scripted outputs pay.
+#[derive(Debug, Default)]
+struct ScriptedOutputs(BTreeMap<OutPoint, u64>);
 
-        Self::hex_to_string(vec_res.as_slice())
+impl ScriptedOutputs {
+    /// Starts from the scripted outputs among `unspent`.
+    fn new(unspent: &UtxoSet) -> Self {
+        Self(
+            unspent
+                .iter()
+                .filter(|(_, output)| output.script.is_some())
+                .map(|(outpoint, output)| (outpoint.clone(), output.amount))
+                .collect(),
+        )
     }
 
-    /// Converts a byte slice to a hexadecimal string.
-    ///
-    /// # Arguments
-    ///
-    /// * `vec_res` - Slice of bytes to convert
+    /// Records the scripted outputs a transaction spends and creates.
     ///
     /// # Returns
     ///
-    /// Hexadecimal string representation of the bytes
-    pub(crate) fn hex_to_string(vec_res: &[u8]) -> String {
-        let mut s = String::with_capacity(vec_res.len() * 2);
-        for b in vec_res {
-            use std::fmt::Write;
-            write!(&mut s, "{b:02x}").unwrap();
+    /// The part of the transaction's debit charged to its sender's account,
+    /// as [`charged`]
+    fn charge(&mut self, transaction: &Transaction, algo: HashAlgo) -> u64 {
+        let unlocked = transaction
+            .inputs
+            .iter()
+            .filter_map(|input| self.0.remove(input))
+            .fold(0, u64::saturating_add);
+        if transaction
+            .outputs
+            .iter()
+            .any(|output| output.script.is_some())
+        {
+            let txid = transaction.txid(algo);
+            for (index, output) in transaction.outputs.iter().enumerate() {
+                if output.script.is_some() {
+                    let outpoint = OutPoint {
+                        txid: txid.clone(),
+                        index: index as u32,
+                    };
+                    self.0.insert(outpoint, output.amount);
+                }
+            }
+        }
+        transaction.debit().saturating_sub(unlocked)
+    }
+}
+
+/// Computes the part of a transaction's debit charged to its sender's account:
+/// the debit minus what the scripted outputs among its inputs, unspent in
+/// `unspent`, pay.
+fn charged(transaction: &Transaction, unspent: &UtxoSet) -> u64 {
+    let unlocked = transaction
+        .inputs
+        .iter()
+        .filter_map(|input| unspent.get(input))
+        .filter(|output| output.script.is_some())
+        .fold(0, |sum: u64, output| sum.saturating_add(output.amount));
+    transaction.debit().saturating_sub(unlocked)
+}
+
+/// How long a block mined by this chain took, reported by
+/// [`Chain::mining_stats`].
+#[derive(Debug, Clone)]
+struct MiningStats {
+    /// Height of the mined block
+    index: u64,
+    /// Hash of the mined block, to tell whether it is still in the chain
+    hash: String,
+    /// Time spent searching for the nonce
+    duration: Duration,
+    /// Number of hashes tried
+    attempts: u64,
+}
+
+/// Transaction entry of a file read by [`Chain::import_transactions`].
+#[derive(Deserialize)]
+struct TransactionRequest {
+    /// Address of the sender
+    sender: String,
+    /// Address of the receiver
+    receiver: String,
+    /// Amount to transfer, in base units
+    amount: u64,
+    /// Optional fee paid to the miner, in base units
+    #[serde(default)]
+    fee: Option<u64>,
+}
+
+/// On-disk representation of a [`Chain`], used by `save_to_file`, `save_bin`,
+/// and their loading counterparts.
+#[derive(Serialize, Deserialize)]
+struct ChainSnapshot {
+    /// Format version of the file, see [`FORMAT_VERSION`]
+    format_version: u32,
+    /// The sequence of validated blocks forming the blockchain
+    chains: Vec<Block>,
+    /// Pending transactions awaiting inclusion in the next block
+    current_transactions: Vec<Transaction>,
+    /// Mining difficulty (number of leading zeros required in block hash)
+    difficulty: u32,
+    /// Address where mining rewards should be sent
+    miner_address: String,
+    /// Base amount awarded to the miner for successfully mining a block, in base
+    /// units
+    reward: u64,
+    /// Number of blocks after which the block subsidy is halved
+    #[serde(default = "default_halving_interval")]
+    halving_interval: u64,
+    /// Number of blocks mined on top of a coinbase before it can be spent
+    #[serde(default)]
+    coinbase_maturity: u64,
+    /// Addresses paid a percentage of each coinbase besides the miner
+    #[serde(default)]
+    reward_split: Vec<(String, u32)>,
+    /// Number of blocks between difficulty adjustments (0 disables retargeting)
+    #[serde(default)]
+    retarget_interval: u64,
+    /// Desired time between blocks used when retargeting difficulty
+    #[serde(default = "default_target_block_time")]
+    target_block_time: Duration,
+    /// Maximum number of pending transactions
+    #[serde(default = "default_max_mempool")]
+    max_mempool: usize,
+    /// Age after which a pending transaction is evicted
+    #[serde(default = "default_mempool_ttl")]
+    mempool_ttl: Duration,
+    /// Maximum number of pending transactions included in a block
+    #[serde(default = "default_max_block_txs")]
+    max_block_txs: usize,
+    /// Maximum length of a block's binary encoding, in bytes
+    #[serde(default = "default_max_block_bytes")]
+    max_block_bytes: usize,
+    /// Maximum number of hashes tried while mining a single block
+    #[serde(default = "default_max_mining_iterations")]
+    max_mining_iterations: u64,
+    /// Hash function used for block headers, transactions, and Merkle trees
+    #[serde(default)]
+    hash_algo: HashAlgo,
+    /// Compact target of new blocks, used instead of the difficulty when set
+    #[serde(default)]
+    target_bits: Option<u32>,
+    /// Whether the difficulty counts leading zero hex digits or bits
+    #[serde(default)]
+    difficulty_unit: DifficultyUnit,
+    /// Whether the miner and transaction addresses must be checksummed
+    #[serde(default)]
+    check_addresses: bool,
+    /// Address whose coins are burned, if any
+    #[serde(default)]
+    burn_address: Option<String>,
+    /// Totals of the transactions of pruned blocks
+    #[serde(default)]
+    pruned: PrunedTotals,
+    /// Trusted block hashes by height
+    #[serde(default)]
+    checkpoints: BTreeMap<u64, String>,
+}
+
+/// Format version of a JSON chain file, read before the rest of it.
+#[derive(Deserialize)]
+struct FormatTag {
+    /// Version the file was saved in; version 1 named it `version`
+    #[serde(default = "default_format_version", alias = "version")]
+    format_version: u32,
+}
+
+/// Returns the format version of snapshots saved before it was recorded.
+const fn default_format_version() -> u32 {
+    1
+}
+
+/// Upgrades a JSON chain file to [`FORMAT_VERSION`], one version at a time.
+///
+/// # Returns
+///
+/// The migrated file, or [`BlockchainError::UnsupportedVersion`] if it was
+/// saved by a later version or no migration exists from its version
+fn migrate_json(mut value: serde_json::Value) -> Result<serde_json::Value, BlockchainError> {
+    let mut version = FormatTag::deserialize(&value)?.format_version;
+    if version > FORMAT_VERSION {
+        return Err(BlockchainError::UnsupportedVersion(version));
+    }
+    while version < FORMAT_VERSION {
+        value = match version {
+            1 => migrate_v1_to_v2(value),
+            2..=7 => retag(value, version + 1),
+            _ => return Err(BlockchainError::UnsupportedVersion(version)),
+        };
+        tracing::debug!(from = version, "chain file migrated");
+        version += 1;
+    }
+    Ok(value)
+}
+
+/// Migrates a version 1 JSON chain file to version 2, which only renamed the
+/// `version` tag to `format_version`.
+fn migrate_v1_to_v2(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(object) = value.as_object_mut() {
+        object.remove("version");
+        object.insert("format_version".to_string(), 2.into());
+    }
+    value
+}
+
+/// Migrates a JSON chain file to a version that only added fields with
+/// defaults, so retagging the file is enough:
+///
+/// * 3 - transaction timestamps and the mempool time to live
+/// * 4 - the block size limit
+/// * 5 - the burn address
+/// * 6 - the coinbase maturity
+/// * 7 - the reward split
+/// * 8 - output scripts
+fn retag(mut value: serde_json::Value, version: u32) -> serde_json::Value {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("format_version".to_string(), version.into());
+    }
+    value
+}
+
+/// Returns the halving interval for snapshots saved before halving existed.
+const fn default_halving_interval() -> u64 {
+    DEFAULT_HALVING_INTERVAL
+}
+
+/// Returns the target block time for snapshots saved before retargeting existed.
+const fn default_target_block_time() -> Duration {
+    DEFAULT_TARGET_BLOCK_TIME
+}
+
+/// Returns the mempool limit for snapshots saved before the limit existed.
+const fn default_max_mempool() -> usize {
+    DEFAULT_MAX_MEMPOOL
+}
+
+/// Returns the mempool TTL for snapshots saved before transactions expired.
+const fn default_mempool_ttl() -> Duration {
+    DEFAULT_MEMPOOL_TTL
+}
+
+/// Returns the block capacity for snapshots saved before the limit existed.
+const fn default_max_block_txs() -> usize {
+    DEFAULT_MAX_BLOCK_TXS
+}
+
+/// Returns the block size limit for snapshots saved before the limit existed.
+const fn default_max_block_bytes() -> usize {
+    DEFAULT_MAX_BLOCK_BYTES
+}
+
+/// Returns the mining iteration cap for snapshots saved before the cap existed.
+const fn default_max_mining_iterations() -> u64 {
+    DEFAULT_MAX_MINING_ITERATIONS
+}
+
+/// Replaces the file at `path` with `bytes` without ever leaving it partially
+/// written.
+///
+/// The bytes are written and synced to a temporary file next to `path`, which
+/// is then renamed over it. `before_rename` runs just before the rename; if it
+/// or any earlier step fails, the temporary file is removed and `path` is left
+/// untouched.
+fn write_atomic(
+    path: &Path,
+    bytes: &[u8],
+    before_rename: impl FnOnce() -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    let written = std::fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|()| before_rename())
+        .and_then(|()| std::fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    written
+}
+
+impl Chain {
+    /// Creates a new blockchain with a genesis block.
+    ///
+    /// Shorthand for [`Chain::from_config`] with every other setting left at its
+    /// default.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner_address` - Address where mining rewards will be sent
+    /// * `difficulty` - Initial mining difficulty (number of leading zeros required in hash),
+    ///   clamped to [`MAX_DIFFICULTY`]
+    /// * `reward` - Optional mining reward in base units (defaults to 50 coins if
+    ///   None)
+    ///
+    /// # Returns
+    ///
+    /// A new Chain instance with a genesis block, or an error if mining the
+    /// genesis block fails
+    pub fn new(
+        miner_address: String,
+        difficulty: u32,
+        reward: Option<u64>,
+    ) -> Result<Self, BlockchainError> {
+        Self::from_config(Self::base_config(miner_address, difficulty, reward))
+    }
+
+    /// Creates a new blockchain that reads block timestamps from the given clock.
+    ///
+    /// The genesis block and every block mined afterwards are stamped with
+    /// `clock.now()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner_address` - Address where mining rewards will be sent
+    /// * `difficulty` - Initial mining difficulty, clamped to [`MAX_DIFFICULTY`]
+    /// * `reward` - Optional mining reward in base units (defaults to 50 coins if
+    ///   None)
+    /// * `clock` - Time source for block timestamps
+    ///
+    /// # Returns
+    ///
+    /// A new Chain instance with a genesis block, or an error if mining the
+    /// genesis block fails
+    pub fn with_clock(
+        miner_address: String,
+        difficulty: u32,
+        reward: Option<u64>,
+        clock: Box<dyn Clock>,
+    ) -> Result<Self, BlockchainError> {
+        Self::from_config(Self::base_config(miner_address, difficulty, reward).clock(clock))
+    }
+
+    /// Creates a new blockchain that hashes with the given algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner_address` - Address where mining rewards will be sent
+    /// * `difficulty` - Initial mining difficulty, clamped to [`MAX_DIFFICULTY`]
+    /// * `reward` - Optional mining reward in base units (defaults to 50 coins if
+    ///   None)
+    /// * `hash_algo` - Hash function for headers, transactions, and Merkle trees
+    ///
+    /// # Returns
+    ///
+    /// A new Chain instance with a genesis block, or an error if mining the
+    /// genesis block fails
+    pub fn with_hash_algo(
+        miner_address: String,
+        difficulty: u32,
+        reward: Option<u64>,
+        hash_algo: HashAlgo,
+    ) -> Result<Self, BlockchainError> {
+        Self::from_config(Self::base_config(miner_address, difficulty, reward).hash_algo(hash_algo))
+    }
+
+    /// Creates a new blockchain whose genesis block has a fixed timestamp.
+    ///
+    /// Chains created with the same arguments have identical genesis blocks, so
+    /// their genesis hashes can be pinned.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner_address` - Address where mining rewards will be sent
+    /// * `difficulty` - Initial mining difficulty, clamped to [`MAX_DIFFICULTY`]
+    /// * `reward` - Optional mining reward in base units (defaults to 50 coins if
+    ///   None)
+    /// * `genesis_timestamp` - Timestamp recorded in the genesis block header
+    ///
+    /// # Returns
+    ///
+    /// A new Chain instance with a genesis block, or an error if mining the
+    /// genesis block fails
+    pub fn with_genesis_timestamp(
+        miner_address: String,
+        difficulty: u32,
+        reward: Option<u64>,
+        genesis_timestamp: DateTime<Utc>,
+    ) -> Result<Self, BlockchainError> {
+        Self::from_config(
+            Self::base_config(miner_address, difficulty, reward)
+                .genesis_timestamp(genesis_timestamp),
+        )
+    }
+
+    /// Returns the settings taken by the positional constructors.
+    fn base_config(miner_address: String, difficulty: u32, reward: Option<u64>) -> ChainConfig {
+        let config = ChainConfig::new(miner_address).difficulty(difficulty);
+        match reward {
+            Some(reward) => config.reward(reward),
+            None => config,
+        }
+    }
+
+    /// Creates a new blockchain from the given settings and mines its genesis
+    /// block.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Settings of the chain; see [`ChainConfig`] for how
+    ///   out-of-range values are handled
+    ///
+    /// # Returns
+    ///
+    /// A new Chain instance with a genesis block, or an error if mining the
+    /// genesis block fails
+    pub fn from_config(config: ChainConfig) -> Result<Self, BlockchainError> {
+        let target_block_time = if config.target_block_time.is_zero() {
+            DEFAULT_TARGET_BLOCK_TIME
+        } else {
+            config.target_block_time
+        };
+        let mempool_ttl = if config.mempool_ttl.is_zero() {
+            DEFAULT_MEMPOOL_TTL
+        } else {
+            config.mempool_ttl
+        };
+        if let Some(bits) = config.target_bits
+            && Target::from_compact(bits).is_none()
+        {
+            return Err(BlockchainError::InvalidTarget(bits));
+        }
+        if config.check_addresses && !address::is_valid(&config.miner_address) {
+            return Err(BlockchainError::InvalidAddress(config.miner_address));
+        }
+        check_reward_split(&config.reward_split, config.check_addresses)?;
+        if config.genesis_message.len() > MAX_MEMO_LEN {
+            return Err(BlockchainError::MemoTooLong {
+                len: config.genesis_message.len(),
+                max: MAX_MEMO_LEN,
+            });
+        }
+        let genesis_timestamp = config
+            .genesis_timestamp
+            .unwrap_or_else(|| config.clock.now());
+        let mut chain = Self {
+            chains: Vec::new(),
+            current_transactions: Vec::new(),
+            difficulty: config
+                .difficulty
+                .min(config.difficulty_unit.max_difficulty()),
+            miner_address: config.miner_address,
+            reward: config.reward,
+            halving_interval: config.halving_interval.max(1),
+            coinbase_maturity: config.coinbase_maturity,
+            reward_split: config.reward_split,
+            retarget_interval: config.retarget_interval,
+            target_block_time,
+            max_mempool: config.max_mempool.max(1),
+            mempool_ttl,
+            max_block_txs: config.max_block_txs.max(1),
+            max_block_bytes: if config.max_block_bytes == 0 {
+                DEFAULT_MAX_BLOCK_BYTES
+            } else {
+                config.max_block_bytes
+            },
+            max_mining_iterations: config.max_mining_iterations.max(1),
+            hash_algo: config.hash_algo,
+            target_bits: config.target_bits,
+            difficulty_unit: config.difficulty_unit,
+            check_addresses: config.check_addresses,
+            burn_address: config.burn_address,
+            mining_threads: config
+                .mining_threads
+                .map_or_else(default_mining_threads, |threads| threads.max(1)),
+            total_work: 0,
+            pruned: PrunedTotals::default(),
+            utxos: UtxoSet::new(),
+            checkpoints: BTreeMap::new(),
+            mining_stats: Vec::new(),
+            quiet: config.quiet,
+            consensus: config.consensus,
+            storage: config.storage,
+            clock: config.clock,
+            on_block: OnBlock::default(),
+            genesis_message: config.genesis_message,
+            audit: config.audit.then(Vec::new),
+        };
+        let stored = chain.storage.load()?;
+        if stored.is_empty() {
+            chain.mine_block_at(genesis_timestamp, &AtomicBool::new(false))?;
+        } else {
+            chain.resume(stored)?;
+        }
+        Ok(chain)
+    }
+
+    /// Adopts the blocks read from the chain's storage, continuing at the
+    /// difficulty of the last one.
+    fn resume(&mut self, blocks: Vec<Block>) -> Result<(), BlockchainError> {
+        self.total_work = Self::chain_work(&blocks);
+        self.chains = blocks;
+        if let Some(tampering) = self.find_tampering() {
+            tracing::warn!(%tampering, "stored chain failed validation");
+            return Err(BlockchainError::InvalidChain);
+        }
+        if let Some(last) = self.chains.last() {
+            self.difficulty = last.header.difficulty;
+        }
+        self.rebuild_utxos();
+        tracing::info!(blocks = self.chains.len(), "chain resumed from storage");
+        Ok(())
+    }
+
+    /// Adds a new transaction to the pending transaction pool.
+    ///
+    /// The amount must not be zero. The sender must be able to cover the amount
+    /// and fee from its [spendable balance](Chain::spendable_balance) minus
+    /// amounts already pending from it.
+    /// The coinbase sender has no balance, so mining rewards cannot be submitted
+    /// as transactions.
+    /// A transaction whose [`Transaction::txid`] is already pending is rejected,
+    /// and so is one whose memo is longer than [`MAX_MEMO_LEN`] bytes. On a
+    /// chain created with [`ChainConfig::check_addresses`], the sender and
+    /// receiver must be checksummed [`address`](crate::address)es, the burn
+    /// address excepted. The burn address set with
+    /// [`ChainConfig::burn_address`] cannot send.
+    ///
+    /// A pending transaction from the same sender paying the same amount to the
+    /// same receiver is replaced by the new one if it pays a strictly higher
+    /// fee, and the new one is rejected otherwise; the sender then needs to
+    /// cover only the replacement.
+    ///
+    /// When the pending pool is full, the lowest-fee pending transaction is
+    /// evicted to make room if the new transaction pays a strictly higher fee.
+    /// The transaction is stamped with the chain's clock, so it expires once it
+    /// has been pending longer than the mempool TTL.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Address of the transaction sender
+    /// * `receiver` - Address of the transaction receiver
+    /// * `amount` - Amount to transfer, in base units
+    /// * `fee` - Optional fee paid to the miner in base units (defaults to 0 if
+    ///   None)
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the transaction was added, or an error describing why the
+    /// amount, fee, sender balance, duplicate check, or pool capacity rejected it
+    pub fn add_transaction(
+        &mut self,
+        sender: String,
+        receiver: String,
+        amount: u64,
+        fee: Option<u64>,
+    ) -> Result<(), BlockchainError> {
+        let fee = fee.unwrap_or(0); // Default fee if not provided
+        let transaction = Transaction::new(sender, receiver, amount)
+            .with_fee(fee)
+            .with_timestamp(self.clock.now());
+        self.submit_transaction(transaction)
+    }
+
+    /// Adds a signed transaction to the pending transaction pool.
+    ///
+    /// Applies the same checks as [`Chain::add_transaction`] and additionally
+    /// requires a valid signature from the sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Transaction signed with [`Transaction::sign`]
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the transaction was added, [`BlockchainError::InvalidSignature`]
+    /// if it is unsigned or its signature is invalid, or any error from the
+    /// other checks
+    pub fn add_signed_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), BlockchainError> {
+        if !transaction.verify() {
+            return Err(BlockchainError::InvalidSignature);
+        }
+        self.submit_transaction(transaction)
+    }
+
+    /// Adds a batch of transactions read from a JSON file to the pending pool.
+    ///
+    /// The file holds an array of `{sender, receiver, amount}` objects with an
+    /// optional `fee`, both in base units. Each entry goes through the checks of
+    /// [`Chain::add_transaction`]; entries that fail them are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Source file path
+    ///
+    /// # Returns
+    ///
+    /// The number of transactions accepted, or an error if the file cannot be
+    /// read or is not a valid array of transactions
+    pub fn import_transactions(&mut self, path: &Path) -> Result<usize, BlockchainError> {
+        let json = std::fs::read_to_string(path)?;
+        let requests: Vec<TransactionRequest> = serde_json::from_str(&json)?;
+        Ok(requests
+            .into_iter()
+            .filter(|request| {
+                self.add_transaction(
+                    request.sender.clone(),
+                    request.receiver.clone(),
+                    request.amount,
+                    request.fee,
+                )
+                .is_ok()
+            })
+            .count())
+    }
+
+    /// Validates a transaction and pushes it to the pending transaction pool.
+    ///
+    /// Applies the checks of [`Chain::add_transaction`] to a transaction built
+    /// by the caller, so fields without an `add_transaction` argument, such as
+    /// a memo or lock time, can be set. A signature, if present, must verify.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Transaction to add
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the transaction was added, or the first check it failed
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), BlockchainError> {
+        let span = tracing::debug_span!(
+            "submit_transaction",
+            sender = %transaction.sender,
+            receiver = %transaction.receiver,
+            amount = transaction.amount,
+            fee = transaction.fee,
+        );
+        let _entered = span.enter();
+        let added = self
+            .audit
+            .is_some()
+            .then(|| AuditEvent::transaction_added(&transaction, self.hash_algo));
+        let result = self.push_transaction(transaction);
+        match &result {
+            Ok(()) => tracing::info!("transaction accepted"),
+            Err(e) => tracing::warn!(error = %e, "transaction rejected"),
+        }
+        if let (Ok(()), Some(event)) = (&result, added) {
+            self.record(event);
+        }
+        result
+    }
+
+    /// Checks a transaction against the pool rules and pushes it, as
+    /// [`Chain::submit_transaction`].
+    fn push_transaction(&mut self, transaction: Transaction) -> Result<(), BlockchainError> {
+        if self.check_addresses {
+            let credited = transaction
+                .credits()
+                .into_iter()
+                .map(|(party, _)| party)
+                .chain(
+                    transaction
+                        .outputs
+                        .iter()
+                        .filter(|output| output.script.is_some())
+                        .map(|output| output.address.as_str()),
+                );
+            for party in std::iter::once(transaction.sender.as_str()).chain(credited) {
+                if !address::is_valid(party) && !self.is_burn_address(party) {
+                    return Err(BlockchainError::InvalidAddress(party.to_string()));
+                }
+            }
+        }
+        if self.is_burn_address(&transaction.sender) {
+            return Err(BlockchainError::BurnSender(transaction.sender));
+        }
+        if let Some(&(_, amount)) = transaction
+            .credits()
+            .iter()
+            .find(|&&(_, amount)| !Self::is_valid_amount(amount))
+        {
+            return Err(BlockchainError::InvalidAmount(amount));
+        }
+        if !transaction.outputs_match() {
+            return Err(BlockchainError::InvalidAmount(transaction.amount));
+        }
+        if let Some(memo) = &transaction.memo
+            && memo.len() > MAX_MEMO_LEN
+        {
+            return Err(BlockchainError::MemoTooLong {
+                len: memo.len(),
+                max: MAX_MEMO_LEN,
+            });
+        }
+        if transaction.signature.is_some() && !transaction.verify() {
+            return Err(BlockchainError::InvalidSignature);
+        }
+        let txid = transaction.txid(self.hash_algo);
+        if self
+            .current_transactions
+            .iter()
+            .any(|pending| pending.txid(self.hash_algo) == txid)
+        {
+            return Err(BlockchainError::DuplicateTransaction(txid));
+        }
+        let Some(position) = self
+            .current_transactions
+            .iter()
+            .position(|pending| pending.conflicts_with(&transaction))
+        else {
+            return self.admit_transaction(transaction);
+        };
+        let replaced = &self.current_transactions[position];
+        if transaction.fee <= replaced.fee {
+            return Err(BlockchainError::ReplacementFeeTooLow {
+                fee: transaction.fee,
+                replaced: replaced.fee,
+            });
+        }
+        // The replaced transaction no longer counts against the sender's funds
+        // or inputs, and is restored if its replacement is rejected.
+        let replaced = self.current_transactions.remove(position);
+        let result = self.admit_transaction(transaction);
+        if result.is_ok() {
+            tracing::info!(sender = %replaced.sender, fee = replaced.fee, "transaction replaced");
+        } else {
+            self.current_transactions.insert(position, replaced);
+        }
+        result
+    }
+
+    /// Checks that the sender can fund a transaction that passed the other pool
+    /// rules and pushes it, evicting the cheapest pending transaction if the
+    /// pool is full.
+    fn admit_transaction(&mut self, transaction: Transaction) -> Result<(), BlockchainError> {
+        self.check_inputs(&transaction)?;
+        let available = self
+            .spendable_balance(&transaction.sender)
+            .saturating_sub(self.pending_outgoing(&transaction.sender));
+        let required = charged(&transaction, &self.utxos);
+        if available < required {
+            return Err(BlockchainError::InsufficientFunds {
+                address: transaction.sender,
+                available,
+                required,
+            });
+        }
+        if self.current_transactions.len() >= self.max_mempool {
+            let cheapest = self
+                .current_transactions
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, t)| t.fee)
+                .filter(|(_, t)| t.fee < transaction.fee)
+                .map(|(i, _)| i);
+            let Some(cheapest) = cheapest else {
+                return Err(BlockchainError::MempoolFull(self.max_mempool));
+            };
+            let evicted = self.current_transactions.remove(cheapest);
+            tracing::info!(sender = %evicted.sender, fee = evicted.fee, "transaction evicted");
+        }
+        self.current_transactions.push(transaction);
+        Ok(())
+    }
+
+    /// Checks that the inputs of a transaction are distinct unspent outputs of
+    /// its sender, not claimed by a pending transaction nor paid by an immature
+    /// coinbase, whose scripts it satisfies and that add up to its outputs plus
+    /// fee.
+    fn check_inputs(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        if transaction.inputs.is_empty() {
+            return Ok(());
+        }
+        let immature: Vec<String> = self
+            .immature_coinbases()
+            .map(|coinbase| coinbase.txid(self.hash_algo))
+            .collect();
+        let mut total: u64 = 0;
+        for (i, input) in transaction.inputs.iter().enumerate() {
+            let claimed = transaction.inputs[..i].contains(input)
+                || immature.contains(&input.txid)
+                || self
+                    .current_transactions
+                    .iter()
+                    .any(|pending| pending.inputs.contains(input));
+            let output = self
+                .utxos
+                .get(input)
+                .filter(|output| !claimed && output.address == transaction.sender)
+                .ok_or_else(|| BlockchainError::UnspendableOutput(input.clone()))?;
+            if let Some(script) = &output.script {
+                script::execute(script, transaction)?;
+            }
+            total = total.saturating_add(output.amount);
+        }
+        if total == transaction.debit() {
+            Ok(())
+        } else {
+            Err(BlockchainError::UnbalancedTransaction {
+                inputs: total,
+                required: transaction.debit(),
+            })
+        }
+    }
+
+    /// Drops pending transactions stamped more than the mempool TTL before
+    /// `now`.
+    ///
+    /// Mining calls this with the new block's timestamp before choosing its
+    /// transactions. Transactions without a timestamp never expire.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Time the ages of pending transactions are measured at
+    ///
+    /// # Returns
+    ///
+    /// The number of transactions dropped
+    pub fn evict_expired(&mut self, now: DateTime<Utc>) -> usize {
+        let ttl = chrono::Duration::from_std(self.mempool_ttl).unwrap_or(chrono::Duration::MAX);
+        let before = self.current_transactions.len();
+        self.current_transactions.retain(|transaction| {
+            transaction
+                .timestamp
+                .is_none_or(|timestamp| now.signed_duration_since(timestamp) <= ttl)
+        });
+        let evicted = before - self.current_transactions.len();
+        if evicted > 0 {
+            tracing::info!(evicted, "expired transactions evicted");
+        }
+        evicted
+    }
+
+    /// Returns the outputs of committed transactions that are not spent yet.
+    pub const fn utxos(&self) -> &UtxoSet {
+        &self.utxos
+    }
+
+    /// Returns the unspent outputs owned by an address, as inputs for
+    /// [`Transaction::spend`].
+    ///
+    /// Outputs claimed by pending transactions are included.
+    pub fn unspent_outputs(&self, address: &str) -> Vec<(OutPoint, TxOut)> {
+        self.utxos
+            .iter()
+            .filter(|(_, output)| output.address == address)
+            .map(|(outpoint, output)| (outpoint.clone(), output.clone()))
+            .collect()
+    }
+
+    /// Computes the balance of an address from all committed transactions.
+    ///
+    /// Incoming amounts are added and outgoing amounts plus fees subtracted. The
+    /// coinbase sender is the emission source and is never debited. A balance
+    /// that would be negative is reported as zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address to compute the balance for
+    ///
+    /// # Returns
+    ///
+    /// The confirmed balance of the address, in base units
+    pub fn get_balance(&self, address: &str) -> u64 {
+        self.balance_in(&self.chains, address)
+    }
+
+    /// Computes the balance of an address after the block at `height`, as
+    /// [`Chain::get_balance`] but counting only blocks up to and including that
+    /// height.
+    ///
+    /// Pruned blocks count as a whole, so any height up to the last pruned block
+    /// reports the balance as of that block.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address to compute the balance for
+    /// * `height` - Index of the last block to count; heights past the tip count
+    ///   the whole chain
+    ///
+    /// # Returns
+    ///
+    /// The balance of the address once the block at `height` was committed, in
+    /// base units
+    pub fn balance_at(&self, address: &str, height: u64) -> u64 {
+        let end = usize::try_from(height).map_or(self.chains.len(), |height| {
+            height.saturating_add(1).min(self.chains.len())
+        });
+        self.balance_in(&self.chains[..end], address)
+    }
+
+    /// Computes the balance of an address from the transactions of `blocks`, a
+    /// prefix of the chain, and those of pruned blocks.
+    fn balance_in(&self, blocks: &[Block], address: &str) -> u64 {
+        let mut incoming = self.pruned.received.get(address).copied().unwrap_or(0);
+        let mut outgoing = self.pruned.sent.get(address).copied().unwrap_or(0);
+        let mut scripted = ScriptedOutputs::new(&self.pruned.utxos);
+        for transaction in blocks.iter().flat_map(|b| &b.transactions) {
+            let charge = scripted.charge(transaction, self.hash_algo);
+            for (receiver, amount) in transaction.credits() {
+                if receiver == address {
+                    incoming = incoming.saturating_add(amount);
+                }
+            }
+            if transaction.sender == address && !transaction.is_coinbase() {
+                outgoing = outgoing.saturating_add(charge);
+            }
+        }
+        incoming.saturating_sub(outgoing)
+    }
+
+    /// Computes the part of an address's confirmed balance it can spend: the
+    /// balance minus its rewards from coinbases that have not matured yet.
+    ///
+    /// A coinbase matures once [`ChainConfig::coinbase_maturity`] blocks have
+    /// been mined on top of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address to compute the balance for
+    ///
+    /// # Returns
+    ///
+    /// The spendable balance of the address, in base units
+    pub fn spendable_balance(&self, address: &str) -> u64 {
+        let immature = self
+            .immature_coinbases()
+            .flat_map(Transaction::credits)
+            .filter(|&(receiver, _)| receiver == address)
+            .fold(0, |sum: u64, (_, amount)| sum.saturating_add(amount));
+        self.get_balance(address).saturating_sub(immature)
+    }
+
+    /// Returns the coinbases of the latest blocks, which have fewer than
+    /// `coinbase_maturity` blocks on top of them.
+    fn immature_coinbases(&self) -> impl Iterator<Item = &Transaction> {
+        let recent = usize::try_from(self.coinbase_maturity).unwrap_or(usize::MAX);
+        self.chains
+            .iter()
+            .rev()
+            .take(recent)
+            .filter_map(|block| block.transactions.first())
+    }
+
+    /// Finds every committed transaction sent from or to an address.
+    ///
+    /// Coinbase rewards paid to the address are included; transactions of
+    /// pruned blocks are not.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address to search for
+    ///
+    /// # Returns
+    ///
+    /// Matching transactions paired with the index of the block containing them
+    pub fn find_transactions(&self, address: &str) -> Vec<(u64, &Transaction)> {
+        self.chains
+            .iter()
+            .flat_map(|b| b.transactions.iter().map(move |t| (b.index, t)))
+            .filter(|(_, t)| {
+                t.sender == address
+                    || t.credits().iter().any(|&(receiver, _)| receiver == address)
+                    || t.outputs.iter().any(|output| output.address == address)
+            })
+            .collect()
+    }
+
+    /// Computes the total amount of coins emitted by coinbase transactions,
+    /// burned ones included.
+    ///
+    /// # Returns
+    ///
+    /// Sum of all block rewards and collected fees paid out across the chain, in
+    /// base units
+    pub fn total_supply(&self) -> u64 {
+        self.chains
+            .iter()
+            .flat_map(|b| &b.transactions)
+            .filter(|t| t.is_coinbase())
+            .fold(self.pruned.supply, |supply, t| {
+                supply.saturating_add(t.amount)
+            })
+    }
+
+    /// Computes the amount of coins sent to the chain's burn address.
+    ///
+    /// # Returns
+    ///
+    /// The confirmed balance of the burn address in base units, or 0 if the
+    /// chain has none
+    pub fn burned_supply(&self) -> u64 {
+        self.burn_address
+            .as_deref()
+            .map_or(0, |address| self.get_balance(address))
+    }
+
+    /// Computes the amount of coins that can still be spent: the
+    /// [`total_supply`](Self::total_supply) minus the
+    /// [`burned_supply`](Self::burned_supply).
+    pub fn circulating_supply(&self) -> u64 {
+        self.total_supply().saturating_sub(self.burned_supply())
+    }
+
+    /// Computes the confirmed balance of every funded address, the stakes
+    /// weighing [`ProofOfStake`](crate::consensus::ProofOfStake) draws.
+    ///
+    /// # Returns
+    ///
+    /// Balance by address, in base units, leaving out empty addresses and the
+    /// burn address
+    pub fn stakes(&self) -> BTreeMap<String, u64> {
+        self.stakes_in(&self.chains)
+    }
+
+    /// Computes the stakes at the end of `blocks`, as [`Chain::stakes`].
+    fn stakes_in(&self, blocks: &[Block]) -> BTreeMap<String, u64> {
+        let mut received = self.pruned.received.clone();
+        let mut sent = self.pruned.sent.clone();
+        let mut scripted = ScriptedOutputs::new(&self.pruned.utxos);
+        for transaction in blocks.iter().flat_map(|b| &b.transactions) {
+            let charge = scripted.charge(transaction, self.hash_algo);
+            for (receiver, amount) in transaction.credits() {
+                let incoming = received.entry(receiver.to_string()).or_default();
+                *incoming = incoming.saturating_add(amount);
+            }
+            if !transaction.is_coinbase() {
+                let outgoing = sent.entry(transaction.sender.clone()).or_default();
+                *outgoing = outgoing.saturating_add(charge);
+            }
+        }
+        received
+            .into_iter()
+            .map(|(address, incoming)| {
+                let outgoing = sent.get(&address).copied().unwrap_or(0);
+                (address, incoming.saturating_sub(outgoing))
+            })
+            .filter(|(address, balance)| *balance > 0 && !self.is_burn_address(address))
+            .collect()
+    }
+
+    /// Replays every transaction of the chain from genesis, checking that no
+    /// sender ever spends more than its balance.
+    ///
+    /// Unlike [`Chain::find_tampering`], which checks how blocks are sealed
+    /// and linked, this checks that the coins moved by the chain add up.
+    /// Pruned blocks are trusted, so the replay starts from their totals.
+    ///
+    /// # Returns
+    ///
+    /// The final balance by address, in base units, leaving out empty
+    /// addresses, or [`BlockchainError::InvalidBlock`] with
+    /// [`Tampering::Overdraft`] for the first block whose transactions
+    /// overdraw a sender
+    pub fn replay(&self) -> Result<BTreeMap<String, u64>, BlockchainError> {
+        let mut balances = self.pruned.received.clone();
+        for (address, &sent) in &self.pruned.sent {
+            let balance = balances.entry(address.clone()).or_default();
+            *balance = balance.saturating_sub(sent);
+        }
+        let mut scripted = ScriptedOutputs::new(&self.pruned.utxos);
+        for (i, block) in self.chains.iter().enumerate() {
+            for transaction in &block.transactions {
+                let charge = scripted.charge(transaction, self.hash_algo);
+                if !transaction.is_coinbase() {
+                    let balance = balances.entry(transaction.sender.clone()).or_default();
+                    *balance = balance
+                        .checked_sub(charge)
+                        .ok_or(BlockchainError::InvalidBlock(Tampering::Overdraft(
+                            i as u64,
+                        )))?;
+                }
+                for (receiver, amount) in transaction.credits() {
+                    let balance = balances.entry(receiver.to_string()).or_default();
+                    *balance = balance.saturating_add(amount);
+                }
+            }
+        }
+        balances.retain(|_, balance| *balance > 0);
+        Ok(balances)
+    }
+
+    /// Returns the address entitled to produce the next block under the chain's
+    /// [`Consensus`], or None if any miner may.
+    ///
+    /// Blocks mined by the chain pay their reward to this producer, falling back
+    /// to the miner address.
+    pub fn producer(&self) -> Option<String> {
+        self.producer_after(&self.chains)
+    }
+
+    /// Returns the producer of a block following `blocks`, as
+    /// [`Chain::producer`].
+    fn producer_after(&self, blocks: &[Block]) -> Option<String> {
+        self.consensus
+            .producer(&self.stakes_in(blocks), blocks.len() as u64)
+    }
+
+    /// Checks whether an amount can be transferred.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the amount is not zero
+    pub const fn is_valid_amount(amount: u64) -> bool {
+        amount > 0
+    }
+
+    /// Sums the amounts and fees of pending transactions sent from an address.
+    fn pending_outgoing(&self, address: &str) -> u64 {
+        self.current_transactions
+            .iter()
+            .filter(|t| t.sender == address)
+            .fold(0, |sum, t| sum.saturating_add(charged(t, &self.utxos)))
+    }
+
+    /// Converts a byte slice to a hexadecimal string.
+    ///
+    /// # Arguments
+    ///
+    /// * `vec_res` - Slice of bytes to convert
+    ///
+    /// # Returns
+    ///
+    /// Hexadecimal string representation of the bytes
+    pub fn hex_to_string(vec_res: &[u8]) -> String {
+        let mut s = String::with_capacity(vec_res.len() * 2);
+        for b in vec_res {
+            use std::fmt::Write;
+            write!(&mut s, "{b:02x}").unwrap();
+        }
+        s
+    }
+
+    /// Converts a hexadecimal string to bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `hex` - Hexadecimal string with two characters per byte
+    ///
+    /// # Returns
+    ///
+    /// The decoded bytes, or None if the string is not valid hex
+    pub fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+        if hex.len() % 2 == 1 {
+            return None;
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+
+    /// Gets the hash of the last block in the chain.
+    ///
+    /// # Returns
+    ///
+    /// Hash of the last block, or a string of zeros if the chain is empty
+    pub fn last_hash(&self) -> Result<String, BlockchainError> {
+        let Some(block) = self.chains.last() else {
+            return Ok(ZERO_HASH.to_string());
+        };
+        Ok(block.hash.clone())
+    }
+
+    /// Updates the mining difficulty.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_difficulty` - New mining difficulty value
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the difficulty was updated, or
+    /// [`BlockchainError::InvalidDifficulty`] if it exceeds the
+    /// [`DifficultyUnit::max_difficulty`] of the chain's unit, [`MAX_DIFFICULTY`]
+    /// for hex digits
+    pub fn update_difficulty(&mut self, new_difficulty: u32) -> Result<(), BlockchainError> {
+        let max = self.difficulty_unit.max_difficulty();
+        if new_difficulty > max {
+            return Err(BlockchainError::InvalidDifficulty {
+                difficulty: new_difficulty,
+                max,
+            });
+        }
+        self.set_difficulty(new_difficulty);
+        Ok(())
+    }
+
+    /// Sets the mining difficulty, recording the change in the audit log.
+    fn set_difficulty(&mut self, difficulty: u32) {
+        if difficulty != self.difficulty {
+            self.record(AuditEvent::DifficultyChanged {
+                from: self.difficulty,
+                to: difficulty,
+            });
+        }
+        self.difficulty = difficulty;
+    }
+
+    /// Updates the mining reward amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_reward` - New mining reward in base units
+    ///
+    /// # Returns
+    ///
+    /// `true` if the reward was successfully updated
+    pub fn update_reward(&mut self, new_reward: u64) -> bool {
+        if new_reward != self.reward {
+            self.record(AuditEvent::RewardChanged {
+                from: self.reward,
+                to: new_reward,
+            });
+        }
+        self.reward = new_reward;
+        true
+    }
+
+    /// Updates the number of blocks between reward halvings.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_interval` - New halving interval in blocks
+    ///
+    /// # Returns
+    ///
+    /// `true` if the interval was successfully updated, `false` if it is zero
+    pub const fn update_halving_interval(&mut self, new_interval: u64) -> bool {
+        if new_interval == 0 {
+            return false;
+        }
+        self.halving_interval = new_interval;
+        true
+    }
+
+    /// Updates the maximum number of pending transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_max_mempool` - New mempool capacity
+    ///
+    /// # Returns
+    ///
+    /// `true` if the limit was successfully updated, `false` if it is zero
+    pub const fn update_max_mempool(&mut self, new_max_mempool: usize) -> bool {
+        if new_max_mempool == 0 {
+            return false;
+        }
+        self.max_mempool = new_max_mempool;
+        true
+    }
+
+    /// Updates the maximum number of pending transactions included in a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_max_block_txs` - New block capacity, not counting the reward
+    ///   transaction
+    ///
+    /// # Returns
+    ///
+    /// `true` if the limit was successfully updated, `false` if it is zero
+    pub const fn update_max_block_txs(&mut self, new_max_block_txs: usize) -> bool {
+        if new_max_block_txs == 0 {
+            return false;
+        }
+        self.max_block_txs = new_max_block_txs;
+        true
+    }
+
+    /// Updates the maximum number of hashes tried while mining a single block.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_max_iterations` - New iteration cap
+    ///
+    /// # Returns
+    ///
+    /// `true` if the cap was successfully updated, `false` if it is zero
+    pub const fn update_max_mining_iterations(&mut self, new_max_iterations: u64) -> bool {
+        if new_max_iterations == 0 {
+            return false;
+        }
+        self.max_mining_iterations = new_max_iterations;
+        true
+    }
+
+    /// Updates the number of worker threads used for mining.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_threads` - New number of threads
+    ///
+    /// # Returns
+    ///
+    /// `true` if the thread count was successfully updated, `false` if it is zero
+    pub const fn update_mining_threads(&mut self, new_threads: usize) -> bool {
+        if new_threads == 0 {
+            return false;
+        }
+        self.mining_threads = new_threads;
+        true
+    }
+
+    /// Configures automatic difficulty retargeting.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Number of blocks between adjustments (0 disables retargeting)
+    /// * `target_block_time` - Desired time between blocks
+    ///
+    /// # Returns
+    ///
+    /// `true` if retargeting was successfully configured, `false` if the target
+    /// block time is zero
+    pub const fn update_retarget(&mut self, interval: u64, target_block_time: Duration) -> bool {
+        if target_block_time.is_zero() {
+            return false;
+        }
+        self.retarget_interval = interval;
+        self.target_block_time = target_block_time;
+        true
+    }
+
+    /// Computes the difficulty required of the next block.
+    ///
+    /// The difficulty only changes when the next block lands on a retarget
+    /// boundary. It then compares the time spanned by the last `retarget_interval`
+    /// blocks with the expected span at `target_block_time`. Since each difficulty
+    /// step makes mining 16 times harder (twice as hard when counting bits), the
+    /// difficulty moves by the rounded base-16 (base-2) logarithm of that ratio,
+    /// at most one step per retarget and never above the unit's
+    /// [`DifficultyUnit::max_difficulty`].
+    fn next_difficulty(&self) -> u32 {
+        self.next_difficulty_after(&self.chains, self.difficulty)
+    }
+
+    /// Computes the difficulty required of a block following `blocks`, the
+    /// last of which left the chain at `difficulty`, as
+    /// [`Chain::next_difficulty`].
+    fn next_difficulty_after(&self, blocks: &[Block], difficulty: u32) -> u32 {
+        let height = blocks.len() as u64;
+        if self.retarget_interval == 0
+            || height == 0
+            || !height.is_multiple_of(self.retarget_interval)
+        {
+            return difficulty;
+        }
+        let first = blocks
+            .len()
+            .saturating_sub(self.retarget_interval as usize + 1);
+        let (Some(first_block), Some(last_block)) = (blocks.get(first), blocks.last()) else {
+            return difficulty;
+        };
+        let gaps = (last_block.index - first_block.index) as f64;
+        if gaps == 0.0 {
+            return difficulty;
+        }
+
+        let actual = (last_block.header.timestamp - first_block.header.timestamp)
+            .to_std()
+            .unwrap_or_default()
+            .as_secs_f64()
+            .max(f64::EPSILON);
+        let expected = self.target_block_time.as_secs_f64() * gaps;
+        let step = ((expected / actual).log2() / f64::from(self.difficulty_unit.bits_per_step()))
+            .round()
+            .clamp(-1.0, 1.0);
+
+        if step > 0.0 {
+            (difficulty + 1).min(self.difficulty_unit.max_difficulty())
+        } else if step < 0.0 {
+            difficulty.saturating_sub(1)
+        } else {
+            difficulty
+        }
+    }
+
+    /// Computes the block subsidy for a block at the given height.
+    ///
+    /// The base reward is halved once every `halving_interval` blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - Index of the block being mined
+    ///
+    /// # Returns
+    ///
+    /// `reward / 2^(height / halving_interval)` in base units, rounded down
+    pub fn block_subsidy(&self, height: u64) -> u64 {
+        let halvings = u32::try_from(height / self.halving_interval).unwrap_or(u32::MAX);
+        self.reward.checked_shr(halvings).unwrap_or(0)
+    }
+
+    /// Creates and mines a new block containing pending transactions and
+    /// appends it to the chain.
+    ///
+    /// Equivalent to [`Chain::build_block`] followed by [`Chain::submit_block`].
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block was generated and added to the chain, or an error if
+    /// hashing or mining failed; pending transactions are kept in that case
+    pub fn generate_new_block(&mut self) -> Result<(), BlockchainError> {
+        self.mine_block_at(self.clock.now(), &AtomicBool::new(false))
+    }
+
+    /// Creates and mines a new block like [`Chain::generate_new_block`], paying
+    /// its mining reward to `miner` instead of the chain's miner address.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner` - Address receiving the reward of this block only
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block was generated and added to the chain,
+    /// [`BlockchainError::InvalidAddress`] if the chain checks addresses and
+    /// `miner` is not checksummed, or an error if hashing or mining failed
+    pub fn generate_new_block_to(&mut self, miner: &str) -> Result<(), BlockchainError> {
+        if self.check_addresses && !address::is_valid(miner) {
+            return Err(BlockchainError::InvalidAddress(miner.to_string()));
+        }
+        let timestamp = self.clock.now();
+        self.evict_expired(timestamp);
+        let (block, stats) = self.build_block_for(timestamp, miner, &AtomicBool::new(false))?;
+        self.submit_mined_block(block, stats)
+    }
+
+    /// Mines `n` blocks in sequence with [`Chain::generate_new_block`].
+    ///
+    /// The first block takes the pending transactions it has room for; the rest
+    /// hold whatever is still pending, often only their mining reward.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Number of blocks to mine
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` once all blocks are appended, or the first mining error; blocks
+    /// mined before the error are kept
+    pub fn mine_n(&mut self, n: u64) -> Result<(), BlockchainError> {
+        for _ in 0..n {
+            self.generate_new_block()?;
+        }
+        Ok(())
+    }
+
+    /// Creates and mines a new block like [`Chain::generate_new_block`], stopping
+    /// early once `cancel` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancel` - Flag that another thread (e.g. a Ctrl-C handler) sets to
+    ///   abort mining
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block was generated and added to the chain,
+    /// [`BlockchainError::Cancelled`] if `cancel` was set first, or another error
+    /// if hashing or mining failed; no block is appended and pending transactions
+    /// are kept unless mining succeeds
+    pub fn generate_new_block_cancellable(
+        &mut self,
+        cancel: &AtomicBool,
+    ) -> Result<(), BlockchainError> {
+        self.mine_block_at(self.clock.now(), cancel)
+    }
+
+    /// Builds, mines, and appends a block with the given header timestamp, as
+    /// [`Chain::generate_new_block_cancellable`].
+    fn mine_block_at(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        cancel: &AtomicBool,
+    ) -> Result<(), BlockchainError> {
+        self.evict_expired(timestamp);
+        let miner = self
+            .producer()
+            .unwrap_or_else(|| self.miner_address.clone());
+        let (block, stats) = self.build_block_for(timestamp, &miner, cancel)?;
+        self.submit_mined_block(block, stats)
+    }
+
+    /// Appends a block mined by this chain, reports it, and records its
+    /// statistics.
+    fn submit_mined_block(
+        &mut self,
+        block: Block,
+        stats: MiningStats,
+    ) -> Result<(), BlockchainError> {
+        if !self.quiet {
+            println!("Last {:#?}", &block);
+        }
+        let index = block.index;
+        let transactions = block.transactions.len();
+        let nonce = block.header.nonce;
+        // The block was stamped by this chain's clock, so its timestamp is the
+        // current time.
+        let now = block.header.timestamp;
+        self.submit_block_at(block, now)?;
+        tracing::info!(
+            index,
+            transactions,
+            nonce,
+            attempts = stats.attempts,
+            "block mined"
+        );
+        self.mining_stats.push(stats);
+        Ok(())
+    }
+
+    /// Creates and mines the next block from pending transactions without
+    /// appending it.
+    ///
+    /// Includes a mining reward transaction paying the block subsidy plus the fees
+    /// of all included transactions, and performs proof-of-work. Pending
+    /// transactions follow the reward transaction ordered by descending fee. At
+    /// most `max_block_txs` of them are included, skipping those locked until
+    /// after the block's timestamp, those that would overdraw their sender's
+    /// confirmed balance together with the ones before them, and those that
+    /// would push the block past the chain's size limit. The block is
+    /// stamped with the chain's clock and mined at the difficulty required at
+    /// its height, which differs from the current one on a retarget boundary.
+    ///
+    /// # Returns
+    ///
+    /// The mined block, ready for [`Chain::submit_block`], or an error if hashing
+    /// or mining failed
+    pub fn build_block(&self) -> Result<Block, BlockchainError> {
+        self.build_block_at(self.clock.now(), &AtomicBool::new(false))
+    }
+
+    /// Builds and mines a block with the given header timestamp, as
+    /// [`Chain::build_block`].
+    fn build_block_at(
+        &self,
+        timestamp: DateTime<Utc>,
+        cancel: &AtomicBool,
+    ) -> Result<Block, BlockchainError> {
+        let miner = self
+            .producer()
+            .unwrap_or_else(|| self.miner_address.clone());
+        self.build_block_for(timestamp, &miner, cancel)
+            .map(|(block, _)| block)
+    }
+
+    /// Splits a coinbase paying `amount` according to the chain's reward split.
+    ///
+    /// # Returns
+    ///
+    /// The miner's output, holding what the split leaves, followed by one
+    /// output for each address of the split
+    fn coinbase_outputs(&self, miner: &str, amount: u64) -> Vec<TxOut> {
+        let mut outputs = vec![TxOut {
+            address: miner.to_string(),
+            amount,
+            script: None,
+        }];
+        for (address, share) in &self.reward_split {
+            let part = (u128::from(amount) * u128::from(*share) / 100) as u64;
+            let Some(rest) = outputs[0].amount.checked_sub(part) else {
+                break;
+            };
+            outputs[0].amount = rest;
+            outputs.push(TxOut {
+                address: address.clone(),
+                amount: part,
+                script: None,
+            });
+        }
+        outputs
+    }
+
+    /// Chooses the pending transactions of a block stamped `timestamp`, in the
+    /// order they are included.
+    ///
+    /// A block holds at most `max_block_txs` transactions, so taking them in
+    /// descending fee order maximizes the fees it collects; ties keep their
+    /// arrival order. Locked transactions, those that would overdraw their
+    /// sender together with the ones before them, and those whose encoding
+    /// does not fit in the `room` bytes left are passed over, letting cheaper
+    /// ones take their place.
+    fn select_transactions(&self, timestamp: DateTime<Utc>, room: usize) -> Vec<&Transaction> {
+        let mut pending: Vec<&Transaction> = self
+            .current_transactions
+            .iter()
+            .filter(|t| t.is_unlocked_at(timestamp))
+            .collect();
+        pending.sort_by_key(|t| std::cmp::Reverse(t.fee));
+        let mut remaining: BTreeMap<&str, u64> = BTreeMap::new();
+        let mut room = room;
+        let mut included = Vec::new();
+        for transaction in pending {
+            if included.len() == self.max_block_txs {
+                break;
+            }
+            let size = encoded_len(transaction);
+            let left = remaining
+                .entry(transaction.sender.as_str())
+                .or_insert_with(|| self.spendable_balance(&transaction.sender));
+            let charge = charged(transaction, &self.utxos);
+            if *left >= charge && size <= room {
+                *left -= charge;
+                room -= size;
+                included.push(transaction);
+            }
+        }
+        included
+    }
+
+    /// Builds and mines a block with the given header timestamp and reward
+    /// address, as [`Chain::build_block`], measuring the mining.
+    fn build_block_for(
+        &self,
+        timestamp: DateTime<Utc>,
+        miner: &str,
+        cancel: &AtomicBool,
+    ) -> Result<(Block, MiningStats), BlockchainError> {
+        let span = tracing::debug_span!("build_block", height = self.chains.len());
+        let _entered = span.enter();
+        let header = BlockHeader {
+            timestamp,
+            nonce: 0,
+            previous_hash: self.last_hash()?,
+            merkle: String::new(),
+            difficulty: self.next_difficulty(),
+            unit: self.difficulty_unit,
+            bits: self.target_bits,
+        };
+
+        let coinbase = |amount| {
+            Transaction::spend(
+                String::from(COINBASE_SENDER),
+                Vec::new(),
+                self.coinbase_outputs(miner, amount),
+            )
+            // Naming the height keeps every coinbase's id, and so its output,
+            // distinct from earlier ones paying the same amount to the same
+            // miner.
+            .with_memo(
+                if self.chains.is_empty() && !self.genesis_message.is_empty() {
+                    self.genesis_message.clone()
+                } else {
+                    format!("block {}", self.chains.len())
+                },
+            )
+        };
+        let mut block = Block {
+            header,
+            index: self.chains.len() as u64,
+            count: u32::MAX,
+            transactions: vec![coinbase(u64::MAX)],
+            hash: ZERO_HASH.to_string(),
+            pruned: false,
+        };
+        // The block is measured with its nonce, count, and reward at their
+        // longest, since mining and fees can lengthen them.
+        block.header.nonce = u64::MAX;
+        block.header.merkle = ZERO_HASH.to_string();
+        let room = self
+            .max_block_bytes
+            .saturating_sub(block.size() + MAX_COUNT_PREFIX_GROWTH);
+        block.header.nonce = 0;
+
+        let included = self.select_transactions(timestamp, room);
+        let fees = included
+            .iter()
+            .fold(0, |fees: u64, t| fees.saturating_add(t.fee));
+        block.transactions = vec![coinbase(
+            self.block_subsidy(self.chains.len() as u64)
+                .saturating_add(fees),
+        )];
+        block.transactions.extend(included.into_iter().cloned());
+        block.count = block.transactions.len() as u32;
+        block.header.merkle = Self::get_merkle(&block.transactions, self.hash_algo);
+        let started = std::time::Instant::now();
+        let attempts = Self::proof_of_work(
+            &mut block.header,
+            self.max_mining_iterations,
+            self.hash_algo,
+            self.mining_threads,
+            cancel,
+            !self.quiet,
+        )?;
+        block.hash = self.hash_algo.hash(&block.header);
+        let stats = MiningStats {
+            index: block.index,
+            hash: block.hash.clone(),
+            duration: started.elapsed(),
+            attempts,
+        };
+        Ok((block, stats))
+    }
+
+    /// Returns how hard the blocks mined by this chain were to mine.
+    ///
+    /// Only blocks mined since the chain was created or loaded, and still part
+    /// of it, are reported.
+    ///
+    /// # Returns
+    ///
+    /// `(index, duration, attempts)` for each such block in mining order: its
+    /// height, the time spent searching for its nonce, and the number of
+    /// hashes tried
+    pub fn mining_stats(&self) -> Vec<(u64, Duration, u64)> {
+        self.mining_stats
+            .iter()
+            .filter(|stats| {
+                usize::try_from(stats.index)
+                    .ok()
+                    .and_then(|index| self.chains.get(index))
+                    .is_some_and(|block| block.hash == stats.hash)
+            })
+            .map(|stats| (stats.index, stats.duration, stats.attempts))
+            .collect()
+    }
+
+    /// Checks whether a block can be appended to the chain, without appending it.
+    ///
+    /// The block must sit at the next height, link to the hash of the last block,
+    /// be stamped no earlier than the last block and after the
+    /// [median time past](Chain::median_time_past), at most
+    /// [`MAX_FUTURE_BLOCK_TIME`] ahead of the chain's clock if it is not the
+    /// genesis block, have a Merkle root matching its
+    /// transactions, start with a single coinbase paying the block subsidy plus
+    /// the fees of its transactions, and carry the difficulty required at its
+    /// height with a header hash that meets it.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Candidate block built by [`Chain::build_block`] or mined
+    ///   elsewhere
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block extends the chain validly, or
+    /// [`BlockchainError::InvalidBlock`] describing the first check it failed
+    pub fn verify_block(&self, block: &Block) -> Result<(), BlockchainError> {
+        self.verify_block_at(block, self.clock.now()).map(|_| ())
+    }
+
+    /// Checks a block as [`Chain::verify_block`], with `now` as the clock
+    /// reading that bounds its timestamp.
+    ///
+    /// # Returns
+    ///
+    /// The unspent outputs once the block is applied, or the first check it
+    /// failed
+    fn verify_block_at(
+        &self,
+        block: &Block,
+        now: DateTime<Utc>,
+    ) -> Result<UtxoSet, BlockchainError> {
+        let index = self.chains.len() as u64;
+        if block.pruned {
+            // Without its transactions there is no coinbase to check.
+            return Err(BlockchainError::InvalidBlock(Tampering::MisplacedCoinbase(
+                index,
+            )));
+        }
+        let previous = self.chains.last().map(|last| last.header.timestamp);
+        Self::check_block(block, index, &self.last_hash()?, previous, self.hash_algo)
+            .map_err(BlockchainError::InvalidBlock)?;
+        self.check_rules(block, &self.chains, self.next_difficulty(), now)
+            .map_err(BlockchainError::InvalidBlock)?;
+        let immature: Vec<String> = self
+            .immature_coinbases()
+            .map(|coinbase| coinbase.txid(self.hash_algo))
+            .collect();
+        let mut unspent = self.utxos.clone();
+        for transaction in &block.transactions {
+            Self::apply_spend(&mut unspent, transaction, &immature, self.hash_algo)
+                .map_err(|_| BlockchainError::InvalidBlock(Tampering::InvalidSpend(index)))?;
+        }
+        Ok(unspent)
+    }
+
+    /// Checks the rules a well-formed block following `blocks` must obey on
+    /// this chain: its size, its timestamp against the median time past and
+    /// `now`, its `difficulty`, what its coinbase pays and to whom, and that
+    /// the burn address sends nothing.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block obeys them, or the first rule it breaks
+    fn check_rules(
+        &self,
+        block: &Block,
+        blocks: &[Block],
+        difficulty: u32,
+        now: DateTime<Utc>,
+    ) -> Result<(), Tampering> {
+        let index = blocks.len() as u64;
+        if block.size() > self.max_block_bytes {
+            return Err(Tampering::TooLarge(index));
+        }
+        if !blocks.is_empty() {
+            if block.header.timestamp <= Self::median_time_of(blocks) {
+                return Err(Tampering::TimeTooOld(index));
+            }
+            let limit = chrono::Duration::from_std(MAX_FUTURE_BLOCK_TIME)
+                .map_or(DateTime::<Utc>::MAX_UTC, |ahead| now + ahead);
+            if block.header.timestamp > limit {
+                return Err(Tampering::TimeTooNew(index));
+            }
+        }
+        if block.header.difficulty != difficulty
+            || block.header.unit != self.difficulty_unit
+            || block.header.bits != self.target_bits
+        {
+            return Err(Tampering::WrongDifficulty(index));
+        }
+        let fees = block.transactions[1..]
+            .iter()
+            .fold(0, |fees: u64, t| fees.saturating_add(t.fee));
+        let coinbase = &block.transactions[0];
+        let owed = self.block_subsidy(index).saturating_add(fees);
+        let paid = coinbase
+            .credits()
+            .iter()
+            .fold(0, |paid: u64, &(_, amount)| paid.saturating_add(amount));
+        if coinbase.amount != owed || paid != owed {
+            return Err(Tampering::WrongCoinbaseAmount(index));
+        }
+        if let Some(producer) = self.producer_after(blocks)
+            && coinbase.receiver != producer
+        {
+            return Err(Tampering::WrongProducer(index));
+        }
+        if block.transactions[1..]
+            .iter()
+            .any(|t| self.is_burn_address(&t.sender))
+        {
+            return Err(Tampering::InvalidSpend(index));
+        }
+        Ok(())
+    }
+
+    /// Validates a mined block with [`Chain::verify_block`] and appends it to
+    /// the chain.
+    ///
+    /// Once appended, the block's transactions are removed from the pending
+    /// pool and the callback set by [`Chain::set_on_block`], if any, is invoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block built by [`Chain::build_block`] or mined elsewhere
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the block was appended, [`BlockchainError::InvalidBlock`]
+    /// describing the first check it failed, or the error of the chain's
+    /// storage if writing the block failed
+    pub fn submit_block(&mut self, block: Block) -> Result<(), BlockchainError> {
+        let now = self.clock.now();
+        self.submit_block_at(block, now)
+    }
+
+    /// Validates and appends a block as [`Chain::submit_block`], with `now` as
+    /// the clock reading that bounds its timestamp.
+    fn submit_block_at(&mut self, block: Block, now: DateTime<Utc>) -> Result<(), BlockchainError> {
+        let unspent = self.verify_block_at(&block, now)?;
+        self.storage.push(&block)?;
+        self.set_difficulty(self.next_difficulty());
+        // Transactions are matched by id, which ignores when each copy was
+        // stamped.
+        for transaction in &block.transactions {
+            let txid = transaction.txid(self.hash_algo);
+            if let Some(position) = self
+                .current_transactions
+                .iter()
+                .position(|pending| pending.txid(self.hash_algo) == txid)
+            {
+                self.current_transactions.remove(position);
+            }
+        }
+        self.utxos = unspent;
+        // Pending spends of outputs the block spent can no longer be mined.
+        self.current_transactions.retain(|pending| {
+            pending
+                .inputs
+                .iter()
+                .all(|input| self.utxos.contains_key(input))
+        });
+        self.total_work = self.total_work.saturating_add(block.header.work());
+        self.record(AuditEvent::BlockAdded {
+            index: block.index,
+            hash: block.hash.clone(),
+            transactions: block.transactions.len(),
+        });
+        self.chains.push(block);
+        if let (Some(hook), Some(block)) = (&self.on_block.0, self.chains.last()) {
+            hook(block);
+        }
+        Ok(())
+    }
+
+    /// Returns the median timestamp of the last [`MEDIAN_TIME_SPAN`] blocks.
+    ///
+    /// A new block must be stamped after it, so a miner cannot rewind the
+    /// chain's time by lying about one timestamp. With an even number of blocks
+    /// the later of the two middle timestamps is used.
+    ///
+    /// # Returns
+    ///
+    /// The median time past, or the earliest representable time for a chain
+    /// without blocks
+    pub fn median_time_past(&self) -> DateTime<Utc> {
+        Self::median_time_of(&self.chains)
+    }
+
+    /// Returns the median time past at the end of `blocks`, as
+    /// [`Chain::median_time_past`].
+    fn median_time_of(blocks: &[Block]) -> DateTime<Utc> {
+        let start = blocks.len().saturating_sub(MEDIAN_TIME_SPAN);
+        let mut timestamps: Vec<_> = blocks[start..]
+            .iter()
+            .map(|block| block.header.timestamp)
+            .collect();
+        timestamps.sort_unstable();
+        timestamps
+            .get(timestamps.len() / 2)
+            .copied()
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// Sums the work of a sequence of blocks.
+    fn chain_work(blocks: &[Block]) -> u128 {
+        blocks
+            .iter()
+            .fold(0, |work, block| work.saturating_add(block.header.work()))
+    }
+
+    /// Calculates the Merkle root of a set of transactions.
+    ///
+    /// Leaves are the digests of the transactions and each parent is the digest
+    /// of its two 32-byte children concatenated. The tree is built level by
+    /// level; a level with an odd number of nodes duplicates its last node.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - List of transactions to include in the Merkle tree
+    /// * `algo` - Hash function for leaves and parent nodes
+    ///
+    /// # Returns
+    ///
+    /// Merkle root hash as a string, or [`ZERO_HASH`] for an empty list
+    pub fn get_merkle(transactions: &[Transaction], algo: HashAlgo) -> String {
+        let mut merkle = Self::merkle_leaves(transactions, algo);
+
+        loop {
+            merkle = Self::merkle_level(merkle, algo);
+            if merkle.len() <= 1 {
+                break;
+            }
+        }
+
+        merkle
+            .pop()
+            .map_or_else(|| ZERO_HASH.to_string(), |root| Self::hex_to_string(&root))
+    }
+
+    /// Builds a Merkle proof for the transaction at `index`.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - Transactions the Merkle root was computed from
+    /// * `index` - Position of the transaction to prove
+    /// * `algo` - Hash function the Merkle root was computed with
+    ///
+    /// # Returns
+    ///
+    /// Sibling hashes from the leaf up to the root, each paired with `true` if
+    /// the sibling is the left node. Empty if `index` is out of range.
+    pub fn merkle_proof(
+        transactions: &[Transaction],
+        index: usize,
+        algo: HashAlgo,
+    ) -> Vec<(String, bool)> {
+        if index >= transactions.len() {
+            return Vec::new();
+        }
+
+        let mut proof = Vec::new();
+        let mut level = Self::merkle_leaves(transactions, algo);
+        let mut index = index;
+
+        loop {
+            if level.len() % 2 == 1 {
+                level.push(level[level.len() - 1]);
+            }
+            let is_left = index % 2 == 1;
+            proof.push((Self::hex_to_string(&level[index ^ 1]), is_left));
+            index /= 2;
+            level = Self::merkle_level(level, algo);
+            if level.len() <= 1 {
+                break;
+            }
+        }
+
+        proof
+    }
+
+    /// Checks a Merkle proof produced by [`Chain::merkle_proof`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Hash of the transaction being proven
+    /// * `proof` - Sibling hashes and left/right flags
+    /// * `root` - Expected Merkle root
+    /// * `algo` - Hash function the Merkle root was computed with
+    ///
+    /// # Returns
+    ///
+    /// `true` if folding the proof over `tx_hash` yields `root`, `false` if it
+    /// does not or any hash is not a valid 32-byte hex digest
+    pub fn verify_merkle_proof(
+        tx_hash: &str,
+        proof: &[(String, bool)],
+        root: &str,
+        algo: HashAlgo,
+    ) -> bool {
+        let decode = |hex: &str| Self::hex_to_bytes(hex)?.try_into().ok();
+        let Some(mut node) = decode(tx_hash) else {
+            return false;
+        };
+        for (sibling, is_left) in proof {
+            let Some(sibling) = decode(sibling) else {
+                return false;
+            };
+            node = if *is_left {
+                Self::merkle_parent(&sibling, &node, algo)
+            } else {
+                Self::merkle_parent(&node, &sibling, algo)
+            };
+        }
+        Self::hex_to_string(&node) == root
+    }
+
+    /// Hashes each transaction into a leaf of the Merkle tree, across threads
+    /// with the `parallel` feature.
+    fn merkle_leaves(transactions: &[Transaction], algo: HashAlgo) -> Vec<[u8; 32]> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            transactions
+                .par_iter()
+                .map(|t| algo.digest_item(t))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            transactions.iter().map(|t| algo.digest_item(t)).collect()
+        }
+    }
+
+    /// Hashes each pair of nodes on a Merkle tree level into the next level,
+    /// across threads with the `parallel` feature.
+    fn merkle_level(mut level: Vec<[u8; 32]>, algo: HashAlgo) -> Vec<[u8; 32]> {
+        if level.len() % 2 == 1 {
+            level.push(level[level.len() - 1]);
+        }
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            level
+                .par_chunks(2)
+                .map(|pair| Self::merkle_parent(&pair[0], &pair[1], algo))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            level
+                .chunks(2)
+                .map(|pair| Self::merkle_parent(&pair[0], &pair[1], algo))
+                .collect()
+        }
+    }
+
+    /// Hashes two child digests into their Merkle parent.
+    fn merkle_parent(left: &[u8; 32], right: &[u8; 32], algo: HashAlgo) -> [u8; 32] {
+        algo.digest(&[left.as_slice(), right.as_slice()].concat())
+    }
+
+    /// Performs proof-of-work mining on a block header.
+    ///
+    /// Repeatedly hashes the header with different nonce values until
+    /// finding a hash that meets the header's [`BlockHeader::target`]: at most
+    /// its compact target if it has one, otherwise with the required number of
+    /// leading zeros. Difficulties above [`MAX_DIFFICULTY`] are clamped.
+    ///
+    /// Difficulty 0 without a compact target accepts the header with its current
+    /// nonce after a single hash, without starting worker threads or drawing a
+    /// progress bar, which makes it a fast and deterministic mode for tests.
+    ///
+    /// The search is split across `threads` workers, each scanning a disjoint
+    /// stride of nonces. The lowest valid nonce wins, so the result is the same
+    /// for any number of threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - Block header to mine
+    /// * `max_iterations` - Maximum number of hashes to try before giving up
+    /// * `algo` - Hash function applied to the header
+    /// * `threads` - Number of worker threads (at least one is used)
+    /// * `cancel` - Flag that aborts the search when set
+    /// * `show_progress` - Whether to draw a progress bar of the hashes tried
+    ///   against the expected [`BlockHeader::work`] and print the hash found
+    ///
+    /// # Returns
+    ///
+    /// The number of hashes tried once the header satisfies its target,
+    /// [`BlockchainError::InvalidTarget`] if its compact target is invalid,
+    /// [`BlockchainError::Cancelled`] if `cancel` was set first,
+    /// [`BlockchainError::MiningExhausted`] if `max_iterations` hashes were tried,
+    /// or [`BlockchainError::NonceExhausted`] if the nonce would overflow
+    pub fn proof_of_work(
+        header: &mut BlockHeader,
+        max_iterations: u64,
+        algo: HashAlgo,
+        threads: usize,
+        cancel: &AtomicBool,
+        show_progress: bool,
+    ) -> Result<u64, BlockchainError> {
+        let difficulty = u64::from(header.difficulty.min(MAX_DIFFICULTY));
+        let span = tracing::debug_span!("proof_of_work", difficulty, bits = header.bits, threads);
+        let _entered = span.enter();
+        if header.difficulty == 0 && header.bits.is_none() && max_iterations > 0 {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(BlockchainError::Cancelled);
+            }
+            tracing::debug!(nonce = header.nonce, hash = %algo.hash(&*header), "nonce found");
+            return Ok(1);
+        }
+        let Some(target) = header.target() else {
+            return Err(BlockchainError::InvalidTarget(
+                header.bits.unwrap_or_default(),
+            ));
+        };
+        let expected = u64::try_from(target.work())
+            .unwrap_or(u64::MAX)
+            .min(max_iterations);
+        let pb = if show_progress {
+            indicatif::ProgressBar::new(expected)
+        } else {
+            indicatif::ProgressBar::hidden()
+        };
+        pb.set_style(
+            indicatif::ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} hashes {msg}",
+                )
+                .unwrap_or_else(|_| indicatif::ProgressStyle::default_bar()),
+        );
+        let result = Self::search_nonce(header, target, max_iterations, algo, threads, cancel, &pb);
+        match &result {
+            Ok(_) => pb.finish_with_message("Mining complete!"),
+            Err(BlockchainError::Cancelled) => pb.abandon_with_message("Mining cancelled!"),
+            Err(_) => pb.abandon_with_message("Mining failed!"),
+        }
+        let (m, attempts) = match result {
+            Ok(found) => found,
+            Err(e) => {
+                tracing::warn!(error = %e, "mining failed");
+                return Err(e);
+            }
+        };
+        tracing::debug!(nonce = header.nonce, hash = %m, attempts, "nonce found");
+        if show_progress {
+            println!("Block hashed: {m}");
+        }
+        Ok(attempts)
+    }
+
+    /// Searches for the lowest nonce, starting at the header's, whose hash meets
+    /// the target.
+    ///
+    /// Worker `t` tries offsets `t`, `t + threads`, `t + 2 * threads`, ... and
+    /// stops once its offset passes the best one found so far or once `cancel` is
+    /// set. Every [`PROGRESS_TICK`] hashes a worker advances `progress`. On
+    /// cancellation the header's nonce is left unchanged; when the search is
+    /// exhausted it is left where a sequential search would have stopped.
+    ///
+    /// Returns the hash found and the number of hashes tried by all workers.
+    fn search_nonce(
+        header: &mut BlockHeader,
+        target: Target,
+        max_iterations: u64,
+        algo: HashAlgo,
+        threads: usize,
+        cancel: &AtomicBool,
+        progress: &indicatif::ProgressBar,
+    ) -> Result<(String, u64), BlockchainError> {
+        let start = header.nonce;
+        let available = u64::MAX - start;
+        let span = max_iterations.min(available.saturating_add(1));
+        let stride = threads.max(1) as u64;
+        let best = AtomicU64::new(u64::MAX);
+        let hashes = AtomicU64::new(0);
+
+        let worker = |first: u64| {
+            let mut candidate = header.clone();
+            let mut offset = first;
+            let mut attempts = 0;
+            while offset < span && offset < best.load(Ordering::Relaxed) {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                attempts += 1;
+                if attempts % PROGRESS_TICK == 0 {
+                    progress.inc(PROGRESS_TICK);
+                }
+                candidate.nonce = start + offset;
+                if target.is_met_by(&algo.digest_item(&candidate)) {
+                    best.fetch_min(offset, Ordering::Relaxed);
+                    break;
+                }
+                offset = offset.saturating_add(stride);
+            }
+            hashes.fetch_add(attempts, Ordering::Relaxed);
+        };
+        std::thread::scope(|scope| {
+            for first in 0..stride {
+                scope.spawn(move || worker(first));
+            }
+        });
+
+        if cancel.load(Ordering::Relaxed) {
+            return Err(BlockchainError::Cancelled);
+        }
+        let best = best.into_inner();
+        if best < span {
+            header.nonce = start + best;
+            return Ok((algo.hash(&*header), hashes.into_inner()));
+        }
+        if max_iterations > available {
+            header.nonce = u64::MAX;
+            return Err(BlockchainError::NonceExhausted);
+        }
+        header.nonce = start + max_iterations;
+        Err(BlockchainError::MiningExhausted {
+            attempts: max_iterations,
+        })
+    }
+
+    /// Checks whether a hash satisfies the given mining difficulty.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - Hexadecimal block header hash
+    /// * `difficulty` - Number of leading `'0'` hex characters required
+    ///
+    /// # Returns
+    ///
+    /// `true` if the first `difficulty` characters of the hash are all `'0'`
+    pub fn meets_difficulty(hash: &str, difficulty: u32) -> bool {
+        hash.chars().take(difficulty as usize).all(|c| c == '0')
+    }
+
+    /// Returns the JSON representation of the latest block.
+    ///
+    /// # Returns
+    ///
+    /// Pretty-printed JSON string of the latest block, or None if the chain is empty
+    pub fn get_latest_block_json(&self) -> Result<Option<String>, BlockchainError> {
+        Ok(self
+            .chains
+            .last()
+            .map(serde_json::to_string_pretty)
+            .transpose()?)
+    }
+
+    /// Verifies the integrity of the whole chain.
+    ///
+    /// # Returns
+    ///
+    /// `true` if [`Chain::find_tampering`] finds no inconsistency
+    pub fn is_valid(&self) -> bool {
+        self.find_tampering().is_none()
+    }
+
+    /// Finds the first block that has been tampered with.
+    ///
+    /// Walks the chain from genesis, checking that every block's index matches its
+    /// position, that its stored count matches its transactions, that its
+    /// `previous_hash` equals the recomputed hash of the prior block's header,
+    /// that it is not stamped earlier than the prior block, that its Merkle root
+    /// matches its transactions, that its only coinbase is its first
+    /// transaction, that none of its transactions is locked past its timestamp,
+    /// that its header hash meets the difficulty stored in the
+    /// header, and that its stored hash matches the recomputed one. The genesis
+    /// block must link to the all-zero hash. Coinbase amounts are checked only
+    /// by [`Chain::submit_block`], since the reward may have changed since
+    /// older blocks were mined.
+    ///
+    /// Blocks at or below the highest checkpoint (see [`Chain::add_checkpoint`])
+    /// are trusted: only their index, link, and stored hash are checked, along
+    /// with the checkpointed hashes themselves.
+    ///
+    /// # Returns
+    ///
+    /// The first inconsistency found, or None if the chain is intact
+    pub fn find_tampering(&self) -> Option<Tampering> {
+        Self::find_tampering_in(&self.chains, self.hash_algo, &self.checkpoints)
+            .or_else(|| self.replay_utxos(&self.chains).err())
+    }
+
+    /// Applies the spends of `blocks`, a prefix of the chain or a replacement
+    /// for it, to the outputs left unspent by pruned blocks.
+    ///
+    /// Spends of blocks at or below the highest checkpoint are trusted and
+    /// applied as far as they go. A block may not spend the coinbases of the
+    /// `coinbase_maturity` blocks before it.
+    ///
+    /// # Returns
+    ///
+    /// The outputs unspent after the last block, or
+    /// [`Tampering::InvalidSpend`] for the first block with an invalid spend
+    fn replay_utxos(&self, blocks: &[Block]) -> Result<UtxoSet, Tampering> {
+        let trusted_up_to = self.checkpoints.keys().next_back().copied();
+        let recent = usize::try_from(self.coinbase_maturity).unwrap_or(usize::MAX);
+        let mut unspent = self.pruned.utxos.clone();
+        for (i, block) in blocks.iter().enumerate() {
+            let index = i as u64;
+            let immature: Vec<String> = blocks[i.saturating_sub(recent)..i]
+                .iter()
+                .filter_map(|block| block.transactions.first())
+                .map(|coinbase| coinbase.txid(self.hash_algo))
+                .collect();
+            for transaction in &block.transactions {
+                if Self::apply_spend(&mut unspent, transaction, &immature, self.hash_algo).is_err()
+                    && trusted_up_to.is_none_or(|height| index > height)
+                {
+                    return Err(Tampering::InvalidSpend(index));
+                }
+            }
+        }
+        Ok(unspent)
+    }
+
+    /// Recomputes the unspent outputs after blocks were removed or replaced.
+    fn rebuild_utxos(&mut self) {
+        match self.replay_utxos(&self.chains) {
+            Ok(unspent) => self.utxos = unspent,
+            Err(tampering) => tracing::warn!(%tampering, "unspent outputs not rebuilt"),
+        }
+    }
+
+    /// Spends the inputs of a transaction and adds its outputs to `unspent`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` once applied, [`BlockchainError::InvalidAmount`] if the amount
+    /// or receiver does not match the outputs,
+    /// [`BlockchainError::UnspendableOutput`] if an input is not an unspent
+    /// output of the sender or is paid by one of the `immature` coinbases, or
+    /// [`BlockchainError::UnbalancedTransaction`] if the inputs do not add up
+    /// to the outputs plus fee; `unspent` may be partly updated on error
+    fn apply_spend(
+        unspent: &mut UtxoSet,
+        transaction: &Transaction,
+        immature: &[String],
+        algo: HashAlgo,
+    ) -> Result<(), BlockchainError> {
+        if !transaction.outputs_match() {
+            return Err(BlockchainError::InvalidAmount(transaction.amount));
+        }
+        if !transaction.inputs.is_empty() {
+            let mut total: u64 = 0;
+            for input in &transaction.inputs {
+                let output = unspent
+                    .remove(input)
+                    .filter(|output| {
+                        output.address == transaction.sender && !immature.contains(&input.txid)
+                    })
+                    .ok_or_else(|| BlockchainError::UnspendableOutput(input.clone()))?;
+                if let Some(script) = &output.script {
+                    script::execute(script, transaction)?;
+                }
+                total = total.saturating_add(output.amount);
+            }
+            if total != transaction.debit() {
+                return Err(BlockchainError::UnbalancedTransaction {
+                    inputs: total,
+                    required: transaction.debit(),
+                });
+            }
+        }
+        let txid = transaction.txid(algo);
+        for (index, output) in transaction.outputs.iter().enumerate() {
+            let outpoint = OutPoint {
+                txid: txid.clone(),
+                index: index as u32,
+            };
+            unspent.insert(outpoint, output.clone());
+        }
+        Ok(())
+    }
+
+    /// Finds the first tampered block of a sequence of blocks, as
+    /// [`Chain::find_tampering`].
+    fn find_tampering_in(
+        blocks: &[Block],
+        algo: HashAlgo,
+        checkpoints: &BTreeMap<u64, String>,
+    ) -> Option<Tampering> {
+        let trusted_up_to = checkpoints.keys().next_back().copied();
+        let mut previous_hash = ZERO_HASH.to_string();
+        let mut previous_timestamp = None;
+        for (i, block) in blocks.iter().enumerate() {
+            let index = i as u64;
+            let checked = if trusted_up_to.is_some_and(|height| index <= height) {
+                Self::check_trusted_block(block, index, &previous_hash, algo)
+            } else {
+                Self::check_block(block, index, &previous_hash, previous_timestamp, algo)
+            };
+            match checked {
+                Ok(hash)
+                    if checkpoints
+                        .get(&index)
+                        .is_some_and(|trusted| *trusted != hash) =>
+                {
+                    return Some(Tampering::CheckpointMismatch(index));
+                }
+                Ok(hash) => previous_hash = hash,
+                Err(tampering) => return Some(tampering),
+            }
+            previous_timestamp = Some(block.header.timestamp);
+        }
+        None
+    }
+
+    /// Checks a block covered by a checkpoint: its index, link, and stored hash.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the block's header, or the first inconsistency found
+    fn check_trusted_block(
+        block: &Block,
+        index: u64,
+        previous_hash: &str,
+        algo: HashAlgo,
+    ) -> Result<String, Tampering> {
+        if block.index != index {
+            return Err(Tampering::IndexMismatch(index));
+        }
+        if block.header.previous_hash != previous_hash {
+            return Err(Tampering::BrokenLink(index));
+        }
+        let hash = algo.hash(&block.header);
+        if block.hash != hash {
+            return Err(Tampering::HashMismatch(index));
+        }
+        Ok(hash)
+    }
+
+    /// Checks a block's index, transaction count, link, timestamp, Merkle root,
+    /// coinbase placement, transaction lock times, proof of work, and stored
+    /// hash.
+    ///
+    /// A pruned block must have no transactions left, and only its header is
+    /// checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block to check
+    /// * `index` - Position the block should occupy in the chain
+    /// * `previous_hash` - Hash of the prior block's header
+    /// * `previous_timestamp` - Timestamp of the prior block, or `None` for genesis
+    /// * `algo` - Hash function of the chain
+    ///
+    /// # Returns
+    ///
+    /// The hash of the block's header, or the first inconsistency found
+    fn check_block(
+        block: &Block,
+        index: u64,
+        previous_hash: &str,
+        previous_timestamp: Option<DateTime<Utc>>,
+        algo: HashAlgo,
+    ) -> Result<String, Tampering> {
+        if block.index != index {
+            return Err(Tampering::IndexMismatch(index));
+        }
+        let expected_count = if block.pruned {
+            0
+        } else {
+            block.count as usize
+        };
+        if block.transactions.len() != expected_count {
+            return Err(Tampering::CountMismatch(index));
+        }
+        if block.header.previous_hash != previous_hash {
+            return Err(Tampering::BrokenLink(index));
+        }
+        if previous_timestamp.is_some_and(|previous| block.header.timestamp < previous) {
+            return Err(Tampering::TimestampOutOfOrder(index));
+        }
+        if !block.pruned {
+            Self::check_body(block, index, algo)?;
+        }
+        let digest = algo.digest_item(&block.header);
+        if !block
+            .header
+            .target()
+            .is_some_and(|target| target.is_met_by(&digest))
+        {
+            return Err(Tampering::WrongDifficulty(index));
+        }
+        let hash = Self::hex_to_string(&digest);
+        if block.hash != hash {
+            return Err(Tampering::HashMismatch(index));
+        }
+        Ok(hash)
+    }
+
+    /// Checks a block's Merkle root, coinbase placement, and transaction lock
+    /// times, as [`Chain::check_block`].
+    fn check_body(block: &Block, index: u64, algo: HashAlgo) -> Result<(), Tampering> {
+        if Self::get_merkle(&block.transactions, algo) != block.header.merkle {
+            return Err(Tampering::MerkleMismatch(index));
+        }
+        if !block
+            .transactions
+            .first()
+            .is_some_and(Transaction::is_coinbase)
+            || block.transactions[1..].iter().any(Transaction::is_coinbase)
+        {
+            return Err(Tampering::MisplacedCoinbase(index));
+        }
+        if !block
+            .transactions
+            .iter()
+            .all(|t| t.is_unlocked_at(block.header.timestamp))
+        {
+            return Err(Tampering::LockedTransaction(index));
+        }
+        Ok(())
+    }
+
+    /// Removes the last block of the chain.
+    ///
+    /// The genesis block and pruned blocks are never removed. The non-coinbase
+    /// transactions of the removed block go back to the pending pool ahead of
+    /// the transactions already pending; each is checked again against the
+    /// shortened chain and discarded if it no longer passes.
+    ///
+    /// # Returns
+    ///
+    /// The removed block, or None if only the genesis block is left, the last
+    /// block is pruned, or removing it from the chain's storage failed
+    pub fn pop_block(&mut self) -> Option<Block> {
+        if self.chains.len() <= 1 || self.chains.last().is_some_and(|b| b.pruned) {
+            return None;
+        }
+        if let Err(e) = self.storage.truncate(self.chains.len() as u64 - 1) {
+            tracing::warn!(error = %e, "removing the block from storage failed");
+            return None;
+        }
+        let block = self.chains.pop()?;
+        self.total_work = self.total_work.saturating_sub(block.header.work());
+        self.rebuild_utxos();
+
+        let restored = block
+            .transactions
+            .iter()
+            .filter(|transaction| !transaction.is_coinbase())
+            .cloned();
+        let pending = std::mem::take(&mut self.current_transactions);
+        for transaction in restored.chain(pending) {
+            // Transactions the shortened chain makes invalid are discarded.
+            let _ = self.submit_transaction(transaction);
+        }
+        tracing::info!(index = block.index, "block popped");
+        Some(block)
+    }
+
+    /// Replaces the blocks of the chain with a competing chain holding more work.
+    ///
+    /// The candidate is adopted only if its total work (see [`Chain::total_work`])
+    /// is strictly greater than this chain's, it passes the checks of
+    /// [`Chain::find_tampering`], and each block it adds obeys the difficulty,
+    /// time, coinbase, and producer rules of [`Chain::verify_block`]. A shorter
+    /// chain mined at higher difficulty can therefore replace a longer one.
+    /// Non-coinbase transactions of the replaced blocks that the candidate does
+    /// not contain go back to the pending pool, and pending transactions the
+    /// candidate confirms are dropped. Each returned transaction is checked
+    /// again against the new chain and discarded if it no longer passes. Mining then continues at the difficulty of the
+    /// candidate's last block.
+    ///
+    /// Once blocks are pruned, the candidate must share them, and no other block
+    /// of the candidate may be pruned; its copies of the shared blocks are
+    /// pruned as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - Blocks of the competing chain, starting at genesis
+    ///
+    /// # Returns
+    ///
+    /// `true` if the candidate was adopted, `false` if it was rejected or
+    /// writing it to the chain's storage failed
+    pub fn replace_chain(&mut self, mut candidate: Vec<Block>) -> bool {
+        let candidate_work = Self::chain_work(&candidate);
+        let pruned = self.chains.iter().take_while(|b| b.pruned).count();
+        let keeps_pruned = candidate.len() >= pruned
+            && self.chains[..pruned]
+                .iter()
+                .zip(&candidate)
+                .all(|(ours, theirs)| ours.hash == theirs.hash)
+            && !candidate[pruned..].iter().any(|b| b.pruned);
+        if candidate_work <= self.total_work
+            || !keeps_pruned
+            || Self::find_tampering_in(&candidate, self.hash_algo, &self.checkpoints).is_some()
+        {
+            return false;
+        }
+
+        let fork = self
+            .chains
+            .iter()
+            .zip(&candidate)
+            .take_while(|(ours, theirs)| ours.hash == theirs.hash)
+            .count();
+        for block in &mut candidate[..pruned] {
+            block.transactions.clear();
+            block.pruned = true;
+        }
+        // The new blocks must obey the rules a submitted block would; a new
+        // genesis block has no rules to obey.
+        let now = self.clock.now();
+        for i in fork.max(1)..candidate.len() {
+            let (blocks, rest) = candidate.split_at(i);
+            let difficulty = self.next_difficulty_after(blocks, blocks[i - 1].header.difficulty);
+            if let Err(tampering) = self.check_rules(&rest[0], blocks, difficulty, now) {
+                tracing::warn!(%tampering, "competing chain rejected");
+                return false;
+            }
+        }
+        let Ok(unspent) = self.replay_utxos(&candidate) else {
+            return false;
+        };
+        // Only an accepted chain reaches storage, so a restart never resumes
+        // from a rejected one. A failed write puts the replaced blocks back.
+        let write = |storage: &mut Box<dyn Storage>, blocks: &[Block]| {
+            storage
+                .truncate(fork as u64)
+                .and_then(|()| blocks.iter().try_for_each(|block| storage.push(block)))
+        };
+        if let Err(e) = write(&mut self.storage, &candidate[fork..]) {
+            tracing::warn!(error = %e, "writing the new chain to storage failed");
+            if let Err(e) = write(&mut self.storage, &self.chains[fork..]) {
+                tracing::error!(error = %e, "restoring the stored chain failed");
+            }
+            return false;
+        }
+        let replaced = std::mem::replace(&mut self.chains, candidate);
+        self.total_work = candidate_work;
+        self.utxos = unspent;
+        // Mining continues at the difficulty of the adopted tip, as on resume.
+        if let Some(last) = self.chains.last() {
+            self.set_difficulty(last.header.difficulty);
+        }
+        // Blocks below the fork are shared, so only the new ones can confirm a
+        // dropped or pending transaction.
+        let confirmed: HashSet<String> = self.chains[fork..]
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .map(|transaction| transaction.txid(self.hash_algo))
+            .collect();
+        let dropped = replaced
+            .into_iter()
+            .skip(fork)
+            .flat_map(|block| block.transactions)
+            .filter(|transaction| !transaction.is_coinbase());
+        let pending = std::mem::take(&mut self.current_transactions);
+        let mut discarded = 0;
+        for transaction in dropped.chain(pending) {
+            if confirmed.contains(&transaction.txid(self.hash_algo)) {
+                continue;
+            }
+            // Transactions the new chain makes invalid are discarded.
+            if self.submit_transaction(transaction).is_err() {
+                discarded += 1;
+            }
+        }
+        if discarded > 0 {
+            tracing::info!(
+                discarded,
+                "transactions invalidated by the new chain discarded"
+            );
+        }
+        true
+    }
+
+    /// Records the trusted hash of the block at `index`.
+    ///
+    /// [`Chain::find_tampering`] then skips the full checks of every block up to
+    /// the highest checkpoint, and fails with [`Tampering::CheckpointMismatch`]
+    /// if the block at a checkpointed height has a different hash. Competing
+    /// chains passed to [`Chain::replace_chain`] must match the checkpoints too.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Height of the checkpointed block
+    /// * `hash` - Expected hash of its header
+    pub fn add_checkpoint(&mut self, index: u64, hash: String) {
+        self.checkpoints.insert(index, hash);
+    }
+
+    /// Returns the checkpointed block hashes by height.
+    pub const fn get_checkpoints(&self) -> &BTreeMap<u64, String> {
+        &self.checkpoints
+    }
+
+    /// Drops the transactions of all but the last `keep_last` blocks, keeping
+    /// their headers.
+    ///
+    /// Pruned blocks are marked (see [`Block::is_pruned`]) and still link the
+    /// chain together, and their headers are still validated, but their bodies
+    /// can no longer be checked. The totals of their transactions are kept, so
+    /// balances and [`Chain::total_supply`] are unchanged. The chain's storage
+    /// keeps the blocks in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_last` - Number of latest blocks whose transactions are kept
+    pub fn prune(&mut self, keep_last: usize) {
+        let end = self.chains.len().saturating_sub(keep_last);
+        let mut pruned = 0;
+        for block in self.chains[..end].iter_mut().filter(|b| !b.pruned) {
+            for transaction in block.transactions.drain(..) {
+                self.pruned.add(&transaction, self.hash_algo);
+            }
+            block.pruned = true;
+            pruned += 1;
+        }
+        tracing::info!(pruned, keep_last, "blocks pruned");
+    }
+
+    /// Saves the chain, its settings, and pending transactions to a JSON file.
+    ///
+    /// The file records its [`FORMAT_VERSION`]. It is written to a temporary
+    /// file in the same directory and then renamed over `path`, so a crash
+    /// leaves either the old or the new file, never a partial one.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination file path
+    pub fn save_to_file(&self, path: &Path) -> Result<(), BlockchainError> {
+        let json = serde_json::to_string_pretty(&self.snapshot())?;
+        write_atomic(path, json.as_bytes(), || Ok(()))?;
+        tracing::info!(path = %path.display(), blocks = self.chains.len(), "chain saved");
+        Ok(())
+    }
+
+    /// Saves the chain like [`Chain::save_to_file`], in the compact binary
+    /// format of `bincode` instead of JSON.
+    ///
+    /// The file starts with a four-byte tag followed by the encoded chain,
+    /// whose first field is the [`FORMAT_VERSION`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination file path
+    pub fn save_bin(&self, path: &Path) -> Result<(), BlockchainError> {
+        let mut bytes = BIN_MAGIC.to_vec();
+        bincode::serde::encode_into_std_write(
+            self.snapshot(),
+            &mut bytes,
+            bincode::config::standard(),
+        )?;
+        write_atomic(path, &bytes, || Ok(()))?;
+        tracing::info!(path = %path.display(), blocks = self.chains.len(), "chain saved");
+        Ok(())
+    }
+
+    /// Captures the chain, its settings, and pending transactions for saving.
+    fn snapshot(&self) -> ChainSnapshot {
+        ChainSnapshot {
+            format_version: FORMAT_VERSION,
+            chains: self.chains.clone(),
+            current_transactions: self.current_transactions.clone(),
+            difficulty: self.difficulty,
+            miner_address: self.miner_address.clone(),
+            reward: self.reward,
+            halving_interval: self.halving_interval,
+            coinbase_maturity: self.coinbase_maturity,
+            reward_split: self.reward_split.clone(),
+            retarget_interval: self.retarget_interval,
+            target_block_time: self.target_block_time,
+            max_mempool: self.max_mempool,
+            mempool_ttl: self.mempool_ttl,
+            max_block_txs: self.max_block_txs,
+            max_block_bytes: self.max_block_bytes,
+            max_mining_iterations: self.max_mining_iterations,
+            hash_algo: self.hash_algo,
+            target_bits: self.target_bits,
+            difficulty_unit: self.difficulty_unit,
+            check_addresses: self.check_addresses,
+            burn_address: self.burn_address.clone(),
+            pruned: self.pruned.clone(),
+            checkpoints: self.checkpoints.clone(),
+        }
+    }
+
+    /// Exports every confirmed transaction to a CSV file.
+    ///
+    /// Writes a `block_index,timestamp,sender,receiver,amount` header followed by
+    /// one row per transaction, in chain order. Coinbase rows have the sender
+    /// [`COINBASE_SENDER`]; timestamps are the block's, in RFC 3339 format, and
+    /// amounts are in coins.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination file path
+    pub fn export_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(out, "block_index,timestamp,sender,receiver,amount")?;
+        for block in &self.chains {
+            let timestamp = block.header.timestamp.to_rfc3339();
+            for transaction in &block.transactions {
+                writeln!(
+                    out,
+                    "{},{timestamp},{},{},{}",
+                    block.index,
+                    Self::csv_field(&transaction.sender),
+                    Self::csv_field(&transaction.receiver),
+                    format_amount(transaction.amount)
+                )?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Quotes a CSV field if it contains a delimiter, quote, or line break.
+    fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\"")).into()
+        } else {
+            field.into()
+        }
+    }
+
+    /// Loads a chain previously written by [`Chain::save_to_file`].
+    ///
+    /// The loaded chain is validated with [`Chain::is_valid`] and rejected if corrupt.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Source file path
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed chain, a [`BlockchainError::Serialization`] error if the
+    /// file is malformed, [`BlockchainError::UnsupportedVersion`] if its format
+    /// version is unknown, or [`BlockchainError::InvalidChain`] if the chain
+    /// fails validation. Files of earlier format versions are migrated.
+    pub fn load_from_file(path: &Path) -> Result<Self, BlockchainError> {
+        let chain = Self::from_json(&std::fs::read(path)?)?;
+        tracing::info!(path = %path.display(), blocks = chain.chains.len(), "chain loaded");
+        Ok(chain)
+    }
+
+    /// Rebuilds a chain from the JSON written by [`Chain::save_to_file`],
+    /// validating it like [`Chain::load_from_file`].
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - Serialized chain
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed chain, a [`BlockchainError::Serialization`] error if the
+    /// JSON is malformed, or [`BlockchainError::InvalidChain`] if the chain fails
+    /// validation
+    pub fn from_json(json: &[u8]) -> Result<Self, BlockchainError> {
+        let value = migrate_json(serde_json::from_slice(json)?)?;
+        Self::from_snapshot(serde_json::from_value(value)?)
+    }
+
+    /// Loads a chain previously written by [`Chain::save_bin`], validating it
+    /// like [`Chain::load_from_file`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Source file path
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed chain, a [`BlockchainError::BinaryDecode`] error if the
+    /// file is malformed, or [`BlockchainError::InvalidChain`] if the chain fails
+    /// validation
+    pub fn load_bin(path: &Path) -> Result<Self, BlockchainError> {
+        let chain = Self::from_bin(&std::fs::read(path)?)?;
+        tracing::info!(path = %path.display(), blocks = chain.chains.len(), "chain loaded");
+        Ok(chain)
+    }
+
+    /// Rebuilds a chain from the bytes written by [`Chain::save_bin`],
+    /// validating it like [`Chain::load_from_file`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized chain
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed chain, a [`BlockchainError::BinaryDecode`] error if the
+    /// bytes are malformed, [`BlockchainError::UnsupportedVersion`] if they lack
+    /// the binary tag (as version 0) or are not of the current format version, or
+    /// [`BlockchainError::InvalidChain`] if the chain fails validation
+    pub fn from_bin(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        let bytes = bytes
+            .strip_prefix(BIN_MAGIC)
+            .ok_or(BlockchainError::UnsupportedVersion(0))?;
+        let config = bincode::config::standard().with_limit::<MAX_BIN_LEN>();
+        let (version, _): (u32, _) = bincode::decode_from_slice(bytes, config)?;
+        // Binary files have no room for the fields later versions added.
+        if version != FORMAT_VERSION {
+            return Err(BlockchainError::UnsupportedVersion(version));
+        }
+        let (snapshot, _) = bincode::serde::decode_from_slice(bytes, config)?;
+        Self::from_snapshot(snapshot)
+    }
+
+    /// Rebuilds and validates a saved chain.
+    ///
+    /// A chain without a genesis block is rejected along with tampered ones and
+    /// those with an invalid compact target. Saved pending transactions are
+    /// submitted again; those the pool rules reject are discarded.
+    fn from_snapshot(mut snapshot: ChainSnapshot) -> Result<Self, BlockchainError> {
+        if snapshot.chains.is_empty() {
+            tracing::warn!("loaded chain has no genesis block");
+            return Err(BlockchainError::InvalidChain);
+        }
+        // Files saved before blocks stored their hash get it filled in here.
+        for block in snapshot.chains.iter_mut().filter(|b| b.hash.is_empty()) {
+            block.hash = snapshot.hash_algo.hash(&block.header);
+        }
+        if let Some(bits) = snapshot.target_bits
+            && Target::from_compact(bits).is_none()
+        {
+            return Err(BlockchainError::InvalidTarget(bits));
+        }
+        check_reward_split(&snapshot.reward_split, snapshot.check_addresses)?;
+        let total_work = Self::chain_work(&snapshot.chains);
+        let mut chain = Self {
+            chains: snapshot.chains,
+            current_transactions: Vec::new(),
+            difficulty: snapshot
+                .difficulty
+                .min(snapshot.difficulty_unit.max_difficulty()),
+            miner_address: snapshot.miner_address,
+            reward: snapshot.reward,
+            halving_interval: snapshot.halving_interval.max(1),
+            coinbase_maturity: snapshot.coinbase_maturity,
+            reward_split: snapshot.reward_split,
+            retarget_interval: snapshot.retarget_interval,
+            target_block_time: if snapshot.target_block_time.is_zero() {
+                DEFAULT_TARGET_BLOCK_TIME
+            } else {
+                snapshot.target_block_time
+            },
+            max_mempool: snapshot.max_mempool.max(1),
+            mempool_ttl: if snapshot.mempool_ttl.is_zero() {
+                DEFAULT_MEMPOOL_TTL
+            } else {
+                snapshot.mempool_ttl
+            },
+            max_block_txs: snapshot.max_block_txs.max(1),
+            max_block_bytes: if snapshot.max_block_bytes == 0 {
+                DEFAULT_MAX_BLOCK_BYTES
+            } else {
+                snapshot.max_block_bytes
+            },
+            max_mining_iterations: snapshot.max_mining_iterations.max(1),
+            hash_algo: snapshot.hash_algo,
+            target_bits: snapshot.target_bits,
+            difficulty_unit: snapshot.difficulty_unit,
+            check_addresses: snapshot.check_addresses,
+            burn_address: snapshot.burn_address,
+            mining_threads: default_mining_threads(),
+            total_work,
+            pruned: snapshot.pruned,
+            utxos: UtxoSet::new(),
+            checkpoints: snapshot.checkpoints,
+            mining_stats: Vec::new(),
+            quiet: false,
+            consensus: Box::new(ProofOfWork),
+            storage: Box::new(MemoryStorage),
+            clock: Box::new(SystemClock),
+            on_block: OnBlock::default(),
+            genesis_message: String::new(),
+            audit: None,
+        };
+        if let Some(tampering) = chain.find_tampering() {
+            tracing::warn!(%tampering, "loaded chain failed validation");
+            return Err(BlockchainError::InvalidChain);
+        }
+        chain.rebuild_utxos();
+        // Pending transactions pass the pool rules again, as if just received.
+        for transaction in snapshot.current_transactions {
+            if let Err(e) = chain.submit_transaction(transaction) {
+                tracing::warn!(error = %e, "saved pending transaction discarded");
+            }
+        }
+        Ok(chain)
+    }
+
+    /// Returns JSON representations of all blocks in the chain.
+    ///
+    /// # Returns
+    ///
+    /// Vector of pretty-printed JSON strings for each block
+    pub fn get_blocks_json(&self) -> Result<Vec<String>, BlockchainError> {
+        Ok(self
+            .chains
+            .iter()
+            .map(serde_json::to_string_pretty)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Returns the current mining difficulty.
+    pub const fn get_difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    /// Returns the maximum number of pending transactions.
+    pub const fn get_max_mempool(&self) -> usize {
+        self.max_mempool
+    }
+
+    /// Returns the age after which a pending transaction is evicted.
+    pub const fn get_mempool_ttl(&self) -> Duration {
+        self.mempool_ttl
+    }
+
+    /// Returns the maximum number of pending transactions included in a block.
+    pub const fn get_max_block_txs(&self) -> usize {
+        self.max_block_txs
+    }
+
+    /// Returns the maximum length of a block's binary encoding, in bytes.
+    pub const fn get_max_block_bytes(&self) -> usize {
+        self.max_block_bytes
+    }
+
+    /// Returns the maximum number of hashes tried while mining a single block.
+    pub const fn get_max_mining_iterations(&self) -> u64 {
+        self.max_mining_iterations
+    }
+
+    /// Returns whether the mining progress bar and mined blocks are hidden.
+    pub const fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Hides or shows the mining progress bar and the mined blocks printed to
+    /// stdout.
+    ///
+    /// # Arguments
+    ///
+    /// * `quiet` - `true` to mine without printing anything
+    pub const fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Turns recording of the chain's operations in its audit log on or off.
+    ///
+    /// Turning recording on starts an empty log unless one is kept already;
+    /// turning it off discards the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit` - `true` to record operations from now on
+    pub fn set_audit(&mut self, audit: bool) {
+        if !audit {
+            self.audit = None;
+        } else if self.audit.is_none() {
+            self.audit = Some(Vec::new());
+        }
+    }
+
+    /// Returns the operations recorded in the audit log, oldest first, or
+    /// nothing if the chain keeps no log.
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        self.audit.as_deref().unwrap_or_default()
+    }
+
+    /// Writes the audit log to a JSON file, as an array of entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination file path
+    pub fn export_audit_log(&self, path: &Path) -> Result<(), BlockchainError> {
+        let json = serde_json::to_vec_pretty(self.audit_log())?;
+        write_atomic(path, &json, || Ok(()))?;
+        Ok(())
+    }
+
+    /// Appends an operation to the audit log, stamped with the chain's clock,
+    /// if the chain keeps one.
+    fn record(&mut self, event: AuditEvent) {
+        if let Some(log) = &mut self.audit {
+            log.push(AuditEntry {
+                timestamp: self.clock.now(),
+                event,
+            });
+        }
+    }
+
+    /// Installs a callback invoked with every block appended by
+    /// [`Chain::submit_block`], including those mined by
+    /// [`Chain::generate_new_block`].
+    ///
+    /// Chains have no callback by default; installing one replaces the
+    /// previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Callback receiving each appended block
+    pub fn set_on_block(&mut self, hook: BlockHook) {
+        self.on_block = OnBlock(Some(hook));
+    }
+
+    /// Replaces the rule choosing the producer of each block.
+    ///
+    /// The rule is not saved with the chain, so loaded chains use
+    /// [`ProofOfWork`] until one is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `consensus` - Rule applied to the next blocks
+    pub fn set_consensus(&mut self, consensus: Box<dyn Consensus>) {
+        self.consensus = consensus;
+    }
+
+    /// Returns the number of worker threads used for mining.
+    pub const fn get_mining_threads(&self) -> usize {
+        self.mining_threads
+    }
+
+    /// Returns the hash function used by this chain.
+    pub const fn get_hash_algo(&self) -> HashAlgo {
+        self.hash_algo
+    }
+
+    /// Returns `true` if the chain only accepts checksummed addresses.
+    pub const fn get_check_addresses(&self) -> bool {
+        self.check_addresses
+    }
+
+    /// Returns the address whose coins the chain burns, if any.
+    pub fn get_burn_address(&self) -> Option<&str> {
+        self.burn_address.as_deref()
+    }
+
+    /// Checks whether `address` is the chain's burn address.
+    fn is_burn_address(&self, address: &str) -> bool {
+        self.burn_address.as_deref() == Some(address)
+    }
+
+    /// Returns the unit of the chain's difficulty.
+    pub const fn get_difficulty_unit(&self) -> DifficultyUnit {
+        self.difficulty_unit
+    }
+
+    /// Returns the compact target of new blocks, or None if they use the
+    /// leading-zero difficulty.
+    pub const fn get_target_bits(&self) -> Option<u32> {
+        self.target_bits
+    }
+
+    /// Returns the current halving interval in blocks.
+    pub const fn get_halving_interval(&self) -> u64 {
+        self.halving_interval
+    }
+
+    /// Returns the addresses paid a percentage of each mined coinbase besides
+    /// the miner, see [`ChainConfig::reward_split`].
+    pub fn get_reward_split(&self) -> &[(String, u32)] {
+        &self.reward_split
+    }
+
+    /// Returns the number of blocks mined on top of a coinbase before it can
+    /// be spent.
+    pub const fn get_coinbase_maturity(&self) -> u64 {
+        self.coinbase_maturity
+    }
+
+    /// Returns the total work of the chain, the sum of [`BlockHeader::work`]
+    /// over its blocks.
+    pub const fn total_work(&self) -> u128 {
+        self.total_work
+    }
+
+    /// Returns the current base mining reward.
+    pub const fn get_reward(&self) -> u64 {
+        self.reward
+    }
+
+    /// Returns the block at the given height.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Height of the block (genesis = 0)
+    ///
+    /// # Returns
+    ///
+    /// The block, or None if the chain has no block at that height
+    pub fn get_block_by_index(&self, index: u64) -> Option<&Block> {
+        self.chains.get(usize::try_from(index).ok()?)
+    }
+
+    /// Returns the block whose header hash, as stored in [`Block::get_hash`],
+    /// is the given value.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - Hexadecimal header hash under the chain's [`HashAlgo`]
+    ///
+    /// # Returns
+    ///
+    /// The first block with a matching header hash, or None if there is none
+    pub fn get_block_by_hash(&self, hash: &str) -> Option<&Block> {
+        self.chains.iter().find(|b| b.hash == hash)
+    }
+
+    /// Finds a committed transaction by its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Transaction id as returned by [`Transaction::txid`] under the
+    ///   chain's [`HashAlgo`]
+    ///
+    /// # Returns
+    ///
+    /// The first committed transaction with that id, or None if no block holds
+    /// one; pending transactions are not searched
+    pub fn get_transaction_by_id(&self, id: &str) -> Option<&Transaction> {
+        self.chains
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .find(|transaction| transaction.txid(self.hash_algo) == id)
+    }
+
+    /// Computes the hash of a whole block: header, index, count, and transactions.
+    ///
+    /// Unlike the header hash, which is the proof-of-work target and what
+    /// `previous_hash` links to, this hash changes with every stored field.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block to hash
+    ///
+    /// # Returns
+    ///
+    /// Hexadecimal hash of the block under the chain's [`HashAlgo`]
+    pub fn block_hash(&self, block: &Block) -> String {
+        self.hash_algo.hash(block)
+    }
+
+    /// Returns a reference to the blockchain.
+    pub const fn get_chain(&self) -> &Vec<Block> {
+        &self.chains
+    }
+
+    /// Returns an iterator over the blocks of the chain, from genesis to the
+    /// latest.
+    pub fn iter(&self) -> std::slice::Iter<'_, Block> {
+        self.chains.iter()
+    }
+
+    /// Returns the number of blocks in the chain, including genesis.
+    pub const fn len(&self) -> usize {
+        self.chains.len()
+    }
+
+    /// Returns `true` if the chain holds no blocks, not even genesis.
+    pub const fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+
+    /// Returns the height of the chain: the index of its latest block, so 0
+    /// for a chain holding only genesis.
+    ///
+    /// An empty chain also has height 0; tell it apart with
+    /// [`Chain::is_empty`].
+    pub const fn height(&self) -> u64 {
+        self.chains.len().saturating_sub(1) as u64
+    }
+
+    /// Returns the genesis block, or None if the chain is empty.
+    pub fn genesis(&self) -> Option<&Block> {
+        self.chains.first()
+    }
+
+    /// Returns the most recently appended block, or None if the chain is empty.
+    pub fn latest(&self) -> Option<&Block> {
+        self.chains.last()
+    }
+
+    /// Returns the transactions waiting to be mined, in submission order.
+    pub fn pending_transactions(&self) -> &[Transaction] {
+        &self.current_transactions
+    }
+
+    /// Removes a transaction from the pending transaction pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Position of the transaction in [`Chain::pending_transactions`]
+    ///
+    /// # Returns
+    ///
+    /// The removed transaction, or None if there is no pending transaction at
+    /// `index`
+    pub fn remove_pending(&mut self, index: usize) -> Option<Transaction> {
+        (index < self.current_transactions.len()).then(|| self.current_transactions.remove(index))
+    }
+}
+
+impl<'a> IntoIterator for &'a Chain {
+    type Item = &'a Block;
+    type IntoIter = std::slice::Iter<'a, Block>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::COIN;
+
+    #[test]
+    fn new_chain_has_genesis_block() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn new_chain_uses_default_reward_when_not_provided() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert_eq!(chain.get_reward(), 50 * COIN);
+    }
+
+    #[test]
+    fn new_chain_uses_custom_reward_when_provided() {
+        let chain = Chain::new("Tilt".to_string(), 1, Some(100 * COIN)).unwrap();
+        assert_eq!(chain.get_reward(), 100 * COIN);
+    }
+
+    #[test]
+    fn custom_reward_is_paid_by_mined_coinbase() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, Some(100 * COIN)).unwrap();
+        chain.generate_new_block().unwrap();
+
+        let coinbase = &chain.get_chain()[1].get_transactions()[0];
+        assert_eq!(coinbase.sender, COINBASE_SENDER);
+        assert_eq!(coinbase.amount, 100 * COIN);
+    }
+
+    #[test]
+    fn transaction_added_successfully() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let result = chain.add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None);
+        assert!(result.is_ok());
+        assert_eq!(chain.current_transactions.len(), 1);
+    }
+
+    #[test]
+    fn block_generation_includes_pending_transactions() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Alice".to_string(), 20 * COIN, None)
+            .unwrap();
+
+        let initial_chain_len = chain.len();
+        chain.generate_new_block().unwrap();
+
+        // The new block should contain the two transactions plus the reward transaction
+        assert_eq!(chain.len(), initial_chain_len + 1);
+        assert_eq!(chain.latest().unwrap().count, 3);
+        // Current transactions should be cleared
+        assert_eq!(chain.current_transactions.len(), 0);
+    }
+
+    #[test]
+    fn hash_produces_consistent_output_for_same_input() {
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 10 * COIN);
+
+        let hash1 = HashAlgo::Sha256.hash(&transaction);
+        let hash2 = HashAlgo::Sha256.hash(&transaction);
+
+        assert_eq!(hash1, hash2);
+    }
+
+    #[test]
+    fn hex_to_string_converts_bytes_to_hex() {
+        let bytes = vec![0, 1, 10, 255];
+        let hex = Chain::hex_to_string(&bytes);
+        assert_eq!(hex, "00010aff");
+    }
+
+    #[test]
+    fn last_hash_returns_hash_of_last_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let last_hash = chain.last_hash().unwrap();
+
+        // Generate a new block and check that last_hash changes
+        chain.generate_new_block().unwrap();
+        let new_last_hash = chain.last_hash().unwrap();
+
+        assert_ne!(last_hash, new_last_hash);
+    }
+
+    #[test]
+    fn update_difficulty_changes_chain_difficulty() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.update_difficulty(2).unwrap();
+        assert_eq!(chain.get_difficulty(), 2);
+    }
+
+    #[test]
+    fn update_reward_changes_miner_reward() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.update_reward(75 * COIN);
+        assert_eq!(chain.get_reward(), 75 * COIN);
+    }
+
+    #[test]
+    fn get_merkle_handles_odd_number_of_transactions() {
+        let transactions = vec![
+            Transaction::new("a".to_string(), "b".to_string(), COIN),
+            Transaction::new("c".to_string(), "d".to_string(), 2 * COIN),
+            Transaction::new("e".to_string(), "f".to_string(), 3 * COIN),
+        ];
+
+        let merkle = Chain::get_merkle(&transactions, HashAlgo::Sha256);
+        assert!(!merkle.is_empty());
+    }
+
+    #[test]
+    fn get_merkle_of_two_transactions_is_stable() {
+        let transactions = vec![
+            Transaction::new("a".to_string(), "b".to_string(), COIN),
+            Transaction::new("c".to_string(), "d".to_string(), 2 * COIN),
+        ];
+
+        assert_eq!(
+            Chain::get_merkle(&transactions, HashAlgo::Sha256),
+            "95c2018b8a2d468097ac91a3c97658d988f68a4ee88c8b279b452c078a26d9fb"
+        );
+    }
+
+    #[test]
+    fn get_merkle_of_empty_list_is_zero_hash() {
+        assert_eq!(Chain::get_merkle(&[], HashAlgo::Sha256), ZERO_HASH);
+    }
+
+    #[test]
+    fn get_latest_block_json_returns_none_for_empty_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.chains.clear(); // Artificially clear the chain
+        assert!(chain.get_latest_block_json().unwrap().is_none());
+    }
+
+    #[test]
+    fn get_blocks_json_returns_expected_number_of_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        let blocks_json = chain.get_blocks_json().unwrap();
+        assert_eq!(blocks_json.len(), 3); // Genesis + 2 new blocks
+    }
+
+    #[test]
+    fn is_valid_accepts_untampered_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn is_valid_detects_tampered_transaction_amount() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[1].transactions[1].amount = 1000 * COIN;
+        assert!(!chain.is_valid());
+    }
+
+    #[test]
+    fn is_valid_detects_tampered_genesis_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[0].header.nonce += 1;
+        assert!(!chain.is_valid());
+    }
+
+    #[test]
+    fn is_valid_detects_broken_previous_hash_link() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[1].header.previous_hash = ZERO_HASH.to_string();
+        assert!(!chain.is_valid());
+    }
+
+    fn unmined_header(difficulty: u32) -> BlockHeader {
+        BlockHeader {
+            timestamp: Utc::now(),
+            nonce: 0,
+            previous_hash: ZERO_HASH.to_string(),
+            merkle: String::new(),
+            difficulty,
+            unit: DifficultyUnit::HexDigits,
+            bits: None,
+        }
+    }
+
+    #[test]
+    fn proof_of_work_finds_hash_with_one_leading_zero() {
+        let mut header = unmined_header(1);
+        Chain::proof_of_work(
+            &mut header,
+            DEFAULT_MAX_MINING_ITERATIONS,
+            HashAlgo::Sha256,
+            1,
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+        assert!(HashAlgo::Sha256.hash(&header).starts_with('0'));
+    }
+
+    #[test]
+    fn proof_of_work_finds_hash_with_two_leading_zeros() {
+        let mut header = unmined_header(2);
+        Chain::proof_of_work(
+            &mut header,
+            DEFAULT_MAX_MINING_ITERATIONS,
+            HashAlgo::Sha256,
+            1,
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+        assert!(HashAlgo::Sha256.hash(&header).starts_with("00"));
+    }
+
+    #[test]
+    fn meets_difficulty_counts_leading_hex_zeros() {
+        assert!(Chain::meets_difficulty("00ab", 2));
+        assert!(!Chain::meets_difficulty("0a0b", 2));
+        assert!(Chain::meets_difficulty("abcd", 0));
+    }
+
+    #[test]
+    fn zero_difficulty_mines_on_first_hash() {
+        let chain = Chain::new("Tilt".to_string(), 0, None).unwrap();
+        assert_eq!(chain.get_chain()[0].get_header().get_nonce(), 0);
+    }
+
+    #[test]
+    fn meets_difficulty_does_not_panic_above_hash_length() {
+        let hash = HashAlgo::Sha256.hash(&unmined_header(100));
+        assert!(!Chain::meets_difficulty(&hash, 100));
+    }
+
+    #[test]
+    fn update_difficulty_rejects_difficulty_above_hash_length() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(matches!(
+            chain.update_difficulty(100),
+            Err(BlockchainError::InvalidDifficulty {
+                difficulty: 100,
+                ..
+            })
+        ));
+        assert_eq!(chain.get_difficulty(), 1);
+    }
+
+    #[test]
+    fn block_round_trips_through_json() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let block = chain.get_chain().last().unwrap();
+        let json = serde_json::to_string(block).unwrap();
+        let decoded: Block = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.header.timestamp, block.header.timestamp);
+        assert_eq!(decoded.header.nonce, block.header.nonce);
+        assert_eq!(decoded.header.previous_hash, block.header.previous_hash);
+        assert_eq!(decoded.header.merkle, block.header.merkle);
+        assert_eq!(decoded.header.difficulty, block.header.difficulty);
+        assert_eq!(decoded.count, block.count);
+        assert_eq!(decoded.transactions, block.transactions);
+        assert_eq!(&decoded, block);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("blockchain-sandbox-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn save_and_load_round_trips_chain() {
+        let path = temp_path("round-trip.json");
+        let mut chain = Chain::new("Tilt".to_string(), 1, Some(25 * COIN)).unwrap();
+        chain.generate_new_block().unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.save_to_file(&path).unwrap();
+
+        let loaded = Chain::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.chains, chain.chains);
+        assert_eq!(loaded.current_transactions, chain.current_transactions);
+        assert_eq!(loaded.difficulty, chain.difficulty);
+        assert_eq!(loaded.miner_address, chain.miner_address);
+        assert_eq!(loaded.reward, chain.reward);
+    }
+
+    #[test]
+    fn load_fills_in_hashes_missing_from_older_files() {
+        let path = temp_path("no-hashes.json");
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.save_to_file(&path).unwrap();
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        for block in json["chains"].as_array_mut().unwrap() {
+            block.as_object_mut().unwrap().remove("hash");
+        }
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let loaded = Chain::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.chains, chain.chains);
+    }
+
+    #[test]
+    fn load_rejects_tampered_chain() {
+        let path = temp_path("tampered.json");
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.chains[1].transactions[0].amount = 1000 * COIN;
+        chain.save_to_file(&path).unwrap();
+
+        let err = Chain::load_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(err, BlockchainError::InvalidChain));
+    }
+
+    #[test]
+    fn get_balance_sums_mining_rewards() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        let expected = chain.get_reward() * 2;
+        assert_eq!(chain.get_balance("Tilt"), expected);
+    }
+
+    #[test]
+    fn get_balance_subtracts_outgoing_and_skips_coinbase_sender() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_balance("Tilt"), 90 * COIN);
+        assert_eq!(chain.get_balance("Bob"), 10 * COIN);
+        assert_eq!(chain.get_balance(COINBASE_SENDER), 0);
+    }
+
+    #[test]
+    fn add_transaction_rejects_sender_without_funds() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(
+            chain
+                .add_transaction("Alice".to_string(), "Bob".to_string(), 10 * COIN, None)
+                .is_err()
+        );
+        assert!(chain.current_transactions.is_empty());
+    }
+
+    #[test]
+    fn add_transaction_accepts_sender_after_mined_reward() {
+        let mut chain = Chain::new("Alice".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Alice".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn add_transaction_counts_pending_outgoing_amounts() {
+        let mut chain = Chain::new("Alice".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Alice".to_string(), "Bob".to_string(), 30 * COIN, None)
+            .unwrap();
+        assert!(
+            chain
+                .add_transaction("Alice".to_string(), "Bob".to_string(), 30 * COIN, None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn add_transaction_rejects_coinbase_sender() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(matches!(
+            chain.add_transaction(
+                COINBASE_SENDER.to_string(),
+                "Bob".to_string(),
+                10 * COIN,
+                None,
+            ),
+            Err(BlockchainError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn add_transaction_rejects_zero_amount() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(
+            chain
+                .add_transaction("Tilt".to_string(), "Bob".to_string(), 0, None)
+                .is_err()
+        );
+        assert!(chain.current_transactions.is_empty());
+    }
+
+    #[test]
+    fn small_amounts_add_up_exactly() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, Some(200 * COIN)).unwrap();
+        for _ in 0..1000 {
+            chain.chains[0].transactions.push(Transaction::new(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                COIN / 10,
+            ));
+        }
+
+        // A thousand tenths of a coin would drift away from 100 as f32.
+        assert_eq!(chain.get_balance("Bob"), 100 * COIN);
+        assert_eq!(chain.get_balance("Tilt"), 100 * COIN);
+    }
+
+    fn test_key() -> (Wallet, String) {
+        let wallet = Wallet::from_secret_hex(&"07".repeat(32)).unwrap();
+        let address = wallet.address();
+        (wallet, address)
+    }
+
+    #[test]
+    fn hex_to_bytes_reverses_hex_to_string() {
+        let bytes = vec![0, 1, 10, 255];
+        assert_eq!(
+            Chain::hex_to_bytes(&Chain::hex_to_string(&bytes)),
+            Some(bytes)
+        );
+        assert_eq!(Chain::hex_to_bytes("abc"), None);
+        assert_eq!(Chain::hex_to_bytes("zz"), None);
+    }
+
+    #[test]
+    fn signed_transaction_is_accepted() {
+        let (key, address) = test_key();
+        let mut chain = Chain::new(address.clone(), 1, None).unwrap();
+        let mut transaction = Transaction::new(address, "Bob".to_string(), 10 * COIN);
+        transaction.sign(&key).unwrap();
+
+        assert!(transaction.verify());
+        chain.add_signed_transaction(transaction).unwrap();
+    }
+
+    #[test]
+    fn signed_transaction_with_tampered_amount_is_rejected() {
+        let (key, address) = test_key();
+        let mut chain = Chain::new(address.clone(), 1, None).unwrap();
+        let mut transaction = Transaction::new(address, "Bob".to_string(), 10 * COIN);
+        transaction.sign(&key).unwrap();
+        transaction.amount = 20 * COIN;
+
+        assert!(!transaction.verify());
+        assert!(matches!(
+            chain.add_signed_transaction(transaction),
+            Err(BlockchainError::InvalidSignature)
+        ));
+        assert!(chain.current_transactions.is_empty());
+    }
+
+    #[test]
+    fn add_signed_transaction_rejects_unsigned_transaction() {
+        let (_, address) = test_key();
+        let mut chain = Chain::new(address.clone(), 1, None).unwrap();
+        let transaction = Transaction::new(address, "Bob".to_string(), 10 * COIN);
+        assert!(matches!(
+            chain.add_signed_transaction(transaction),
+            Err(BlockchainError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn coinbase_reward_is_unsigned() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.get_chain()[0].transactions[0].signature.is_none());
+    }
+
+    #[test]
+    fn coinbase_collects_reward_plus_transaction_fees() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                10 * COIN,
+                Some(3 * COIN / 2),
+            )
+            .unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Alice".to_string(),
+                5 * COIN,
+                Some(COIN / 2),
+            )
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let coinbase = &chain.get_chain().last().unwrap().transactions[0];
+        assert_eq!(coinbase.amount, chain.get_reward() + 2 * COIN);
+    }
+
+    #[test]
+    fn add_transaction_requires_funds_for_amount_plus_fee() {
+        let mut chain = Chain::new("Alice".to_string(), 1, None).unwrap();
+        assert!(
+            chain
+                .add_transaction(
+                    "Alice".to_string(),
+                    "Bob".to_string(),
+                    49 * COIN,
+                    Some(2 * COIN)
+                )
+                .is_err()
+        );
+        chain
+            .add_transaction(
+                "Alice".to_string(),
+                "Bob".to_string(),
+                48 * COIN,
+                Some(2 * COIN),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn get_balance_debits_fee_from_sender() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                10 * COIN,
+                Some(2 * COIN),
+            )
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_balance("Bob"), 10 * COIN);
+        // 50 genesis - 12 sent + 52 coinbase including the fee
+        assert_eq!(chain.get_balance("Tilt"), 90 * COIN);
+    }
+
+    #[test]
+    fn genesis_block_has_index_zero() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert_eq!(chain.get_block_by_index(0).unwrap().get_index(), 0);
+    }
+
+    #[test]
+    fn third_block_has_index_two() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_block_by_index(2).unwrap().get_index(), 2);
+        assert!(chain.get_block_by_index(3).is_none());
+    }
+
+    #[test]
+    fn is_valid_detects_tampered_block_index() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[1].index = 5;
+        assert!(!chain.is_valid());
+    }
+
+    #[test]
+    fn get_block_by_hash_finds_mined_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        let block = chain.get_chain().last().unwrap();
+        let header_hash = HashAlgo::Sha256.hash(&block.header);
+        assert_eq!(chain.get_block_by_hash(&header_hash), Some(block));
+        assert!(chain.get_block_by_hash(&chain.block_hash(block)).is_none());
+        assert!(chain.get_block_by_hash(ZERO_HASH).is_none());
+    }
+
+    #[test]
+    fn find_transactions_includes_coinbase_rewards() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        let found = chain.find_transactions("Tilt");
+        assert_eq!(found.len(), chain.get_chain().len());
+        assert_eq!(
+            found.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn find_transactions_matches_sender_and_receiver() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.find_transactions("Bob").len(), 1);
+        assert_eq!(chain.find_transactions("Tilt").len(), 3);
+        assert!(chain.find_transactions("Nobody").is_empty());
+    }
+
+    #[test]
+    fn total_supply_equals_blocks_times_reward() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, Some(25 * COIN)).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        let expected = chain.get_chain().len() as u64 * 25 * COIN;
+        assert_eq!(chain.total_supply(), expected);
+    }
+
+    #[test]
+    fn block_subsidy_halves_every_interval() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_halving_interval(2));
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        let subsidies: Vec<u64> = chain
+            .get_chain()
+            .iter()
+            .map(|b| b.transactions[0].amount)
+            .collect();
+        assert_eq!(subsidies, vec![50 * COIN, 50 * COIN, 25 * COIN, 25 * COIN]);
+    }
+
+    #[test]
+    fn update_halving_interval_rejects_zero() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(!chain.update_halving_interval(0));
+        assert_eq!(chain.get_halving_interval(), DEFAULT_HALVING_INTERVAL);
+    }
+
+    /// Spaces block timestamps evenly, ending at the last block's so the next
+    /// block mined is not stamped before it.
+    fn space_block_timestamps(chain: &mut Chain, spacing: chrono::Duration) {
+        let end = chain.chains.last().unwrap().header.timestamp;
+        for (i, block) in chain.chains.iter_mut().rev().enumerate() {
+            block.header.timestamp = end - spacing * i32::try_from(i).unwrap();
+        }
+    }
+
+    #[test]
+    fn retarget_raises_difficulty_after_fast_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_retarget(2, Duration::from_mins(1)));
+        chain.generate_new_block().unwrap();
+        space_block_timestamps(&mut chain, chrono::Duration::seconds(1));
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_difficulty(), 2);
+        assert_eq!(chain.get_chain().last().unwrap().header.difficulty, 2);
+    }
+
+    #[test]
+    fn retarget_lowers_difficulty_after_slow_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_retarget(2, Duration::from_mins(1)));
+        chain.generate_new_block().unwrap();
+        space_block_timestamps(&mut chain, chrono::Duration::hours(1));
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_difficulty(), 0);
+    }
+
+    #[test]
+    fn retarget_only_applies_on_interval_boundary() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_retarget(3, Duration::from_mins(1)));
+        chain.generate_new_block().unwrap();
+        space_block_timestamps(&mut chain, chrono::Duration::seconds(1));
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_difficulty(), 1);
+    }
+
+    #[test]
+    fn update_retarget_rejects_zero_target_block_time() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(!chain.update_retarget(2, Duration::ZERO));
+    }
+
+    #[test]
+    fn add_transaction_rejects_when_mempool_is_full() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_max_mempool(2));
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 2 * COIN, None)
+            .unwrap();
+
+        assert!(
+            chain
+                .add_transaction("Tilt".to_string(), "Bob".to_string(), 3 * COIN, None)
+                .is_err()
+        );
+        assert_eq!(chain.current_transactions.len(), 2);
+    }
+
+    #[test]
+    fn full_mempool_evicts_cheapest_transaction_for_higher_fee() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_max_mempool(2));
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, Some(COIN / 2))
+            .unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                2 * COIN,
+                Some(COIN / 10),
+            )
+            .unwrap();
+
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 3 * COIN, Some(COIN))
+            .unwrap();
+        let amounts: Vec<u64> = chain
+            .current_transactions
+            .iter()
+            .map(|t| t.amount)
+            .collect();
+        assert_eq!(amounts, vec![COIN, 3 * COIN]);
+    }
+
+    #[test]
+    fn update_max_mempool_rejects_zero() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(!chain.update_max_mempool(0));
+        assert_eq!(chain.get_max_mempool(), DEFAULT_MAX_MEMPOOL);
+    }
+
+    #[test]
+    fn mined_block_orders_transactions_by_descending_fee() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, Some(COIN / 10))
+            .unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                2 * COIN,
+                Some(3 * COIN / 10),
+            )
+            .unwrap();
+        chain
+            .add_transaction(
+                "Tilt".to_string(),
+                "Bob".to_string(),
+                3 * COIN,
+                Some(COIN / 5),
+            )
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let transactions = chain.get_block_by_index(1).unwrap().get_transactions();
+        assert_eq!(transactions[0].sender, COINBASE_SENDER);
+        let fees: Vec<u64> = transactions[1..].iter().map(|t| t.fee).collect();
+        assert_eq!(fees, vec![3 * COIN / 10, COIN / 5, COIN / 10]);
+    }
+
+    #[test]
+    fn block_capacity_leaves_excess_transactions_pending() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_max_block_txs(2));
+        for amount in 1..=5 {
+            chain
+                .add_transaction("Tilt".to_string(), "Bob".to_string(), amount * COIN, None)
+                .unwrap();
+        }
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(
+            chain
+                .get_block_by_index(1)
+                .unwrap()
+                .get_transactions()
+                .len(),
+            3
+        );
+        assert_eq!(chain.current_transactions.len(), 3);
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(
+            chain
+                .get_block_by_index(2)
+                .unwrap()
+                .get_transactions()
+                .len(),
+            3
+        );
+        assert_eq!(chain.current_transactions.len(), 1);
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(
+            chain
+                .get_block_by_index(3)
+                .unwrap()
+                .get_transactions()
+                .len(),
+            2
+        );
+        assert!(chain.current_transactions.is_empty());
+        assert_eq!(chain.get_balance("Bob"), 15 * COIN);
+    }
+
+    #[test]
+    fn merkle_proof_verifies_every_transaction() {
+        let transactions = vec![
+            Transaction::new("a".to_string(), "b".to_string(), COIN),
+            Transaction::new("c".to_string(), "d".to_string(), 2 * COIN),
+            Transaction::new("e".to_string(), "f".to_string(), 3 * COIN),
+        ];
+        let root = Chain::get_merkle(&transactions, HashAlgo::Sha256);
+
+        for (index, transaction) in transactions.iter().enumerate() {
+            let proof = Chain::merkle_proof(&transactions, index, HashAlgo::Sha256);
+            assert!(Chain::verify_merkle_proof(
+                &HashAlgo::Sha256.hash(transaction),
+                &proof,
+                &root,
+                HashAlgo::Sha256
+            ));
+        }
+    }
+
+    #[test]
+    fn merkle_proof_rejects_wrong_transaction() {
+        let transactions = vec![
+            Transaction::new("a".to_string(), "b".to_string(), COIN),
+            Transaction::new("c".to_string(), "d".to_string(), 2 * COIN),
+            Transaction::new("e".to_string(), "f".to_string(), 3 * COIN),
+        ];
+        let root = Chain::get_merkle(&transactions, HashAlgo::Sha256);
+
+        let proof = Chain::merkle_proof(&transactions, 0, HashAlgo::Sha256);
+        assert!(!Chain::verify_merkle_proof(
+            &HashAlgo::Sha256.hash(&transactions[1]),
+            &proof,
+            &root,
+            HashAlgo::Sha256
+        ));
+        assert!(Chain::merkle_proof(&transactions, 3, HashAlgo::Sha256).is_empty());
+    }
+
+    #[test]
+    fn header_hash_is_repeatable_and_covers_every_field() {
+        let header = unmined_header(1);
+        assert_eq!(
+            HashAlgo::Sha256.hash(&header),
+            HashAlgo::Sha256.hash(&header.clone())
+        );
+
+        let mut changed = vec![header.clone(); 6];
+        changed[0].timestamp += chrono::Duration::nanoseconds(1);
+        changed[1].nonce += 1;
+        changed[2].previous_hash.push('0');
+        changed[3].merkle.push('0');
+        changed[4].difficulty += 1;
+        changed[5].bits = Some(0x1d00_ffff);
+        for other in &changed {
+            assert_ne!(HashAlgo::Sha256.hash(other), HashAlgo::Sha256.hash(&header));
+        }
+    }
+
+    #[test]
+    fn transaction_hash_is_repeatable_and_covers_every_field() {
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 10 * COIN);
+        assert_eq!(
+            HashAlgo::Sha256.hash(&transaction),
+            HashAlgo::Sha256.hash(&transaction.clone())
+        );
+
+        let mut changed = vec![transaction.clone(); 7];
+        changed[0].sender.push('!');
+        changed[1].receiver.push('!');
+        changed[2].amount += COIN;
+        changed[3].fee += COIN;
+        changed[4].signature = Some(Vec::new());
+        changed[5].lock_until = Some(DateTime::UNIX_EPOCH);
+        changed[6].memo = Some(String::new());
+        for other in &changed {
+            assert_ne!(
+                HashAlgo::Sha256.hash(other),
+                HashAlgo::Sha256.hash(&transaction)
+            );
+        }
+    }
+
+    #[test]
+    fn add_transaction_reports_insufficient_funds() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let err = chain
+            .add_transaction("Alice".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BlockchainError::InsufficientFunds { ref address, .. } if address == "Alice"
+        ));
+    }
+
+    #[test]
+    fn add_transaction_reports_full_mempool() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_max_mempool(1));
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        assert!(matches!(
+            chain.add_transaction("Tilt".to_string(), "Carol".to_string(), COIN, None),
+            Err(BlockchainError::MempoolFull(1))
+        ));
+    }
+
+    #[test]
+    fn load_reports_missing_file_as_io_error() {
+        let err = Chain::load_from_file(&temp_path("missing.json")).unwrap_err();
+        assert!(matches!(err, BlockchainError::Io(_)));
+    }
+
+    #[test]
+    fn find_tampering_returns_none_for_untampered_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.find_tampering(), None);
+    }
+
+    #[test]
+    fn find_tampering_reports_merkle_mismatch_at_block_two() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[2].header.merkle = ZERO_HASH.to_string();
+        let tampering = chain.find_tampering().unwrap();
+        assert_eq!(tampering, Tampering::MerkleMismatch(2));
+        assert_eq!(tampering.index(), 2);
+    }
+
+    #[test]
+    fn find_tampering_reports_broken_link_after_modified_header() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        // Re-mine the modified header so only the link to it breaks.
+        let header = &mut chain.chains[1].header;
+        header.nonce += 1;
+        Chain::proof_of_work(
+            header,
+            DEFAULT_MAX_MINING_ITERATIONS,
+            HashAlgo::Sha256,
+            1,
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+        chain.chains[1].hash = HashAlgo::Sha256.hash(&chain.chains[1].header);
+        assert_eq!(chain.find_tampering(), Some(Tampering::BrokenLink(2)));
+    }
+
+    #[test]
+    fn proof_of_work_gives_up_after_max_iterations() {
+        let mut header = unmined_header(MAX_DIFFICULTY);
+        assert!(matches!(
+            Chain::proof_of_work(
+                &mut header,
+                1000,
+                HashAlgo::Sha256,
+                4,
+                &AtomicBool::new(false),
+                false
+            ),
+            Err(BlockchainError::MiningExhausted { attempts: 1000 })
+        ));
+        assert_eq!(header.nonce, 1000);
+    }
+
+    #[test]
+    fn proof_of_work_reports_nonce_exhaustion() {
+        let mut header = unmined_header(MAX_DIFFICULTY);
+        header.nonce = u64::MAX - 1;
+        assert!(matches!(
+            Chain::proof_of_work(
+                &mut header,
+                DEFAULT_MAX_MINING_ITERATIONS,
+                HashAlgo::Sha256,
+                4,
+                &AtomicBool::new(false),
+                false
+            ),
+            Err(BlockchainError::NonceExhausted)
+        ));
+        assert_eq!(header.nonce, u64::MAX);
+    }
+
+    #[test]
+    fn failed_mining_keeps_pending_transactions() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.update_difficulty(MAX_DIFFICULTY).unwrap();
+        assert!(chain.update_max_mining_iterations(10));
+
+        assert!(chain.generate_new_block().is_err());
+        assert_eq!(chain.get_chain().len(), 1);
+        assert_eq!(chain.current_transactions.len(), 1);
+    }
+
+    #[test]
+    fn fixed_genesis_timestamp_gives_identical_genesis_hashes() {
+        let timestamp = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let first = Chain::with_genesis_timestamp("Tilt".to_string(), 1, None, timestamp).unwrap();
+        let second = Chain::with_genesis_timestamp("Tilt".to_string(), 1, None, timestamp).unwrap();
+
+        assert_eq!(first.get_chain()[0].header.timestamp, timestamp);
+        assert_eq!(first.last_hash().unwrap(), second.last_hash().unwrap());
+    }
+
+    #[test]
+    fn transaction_hashes_differ_per_algorithm() {
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 10 * COIN);
+        let hashes: Vec<String> = [HashAlgo::Sha256, HashAlgo::Sha3_256, HashAlgo::Blake3]
+            .iter()
+            .map(|algo| algo.hash(&transaction))
+            .collect();
+
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_ne!(hashes[1], hashes[2]);
+    }
+
+    #[test]
+    fn mining_succeeds_under_each_algorithm() {
+        for algo in [
+            HashAlgo::Sha256,
+            HashAlgo::Sha256d,
+            HashAlgo::Sha3_256,
+            HashAlgo::Blake3,
+        ] {
+            let mut chain = Chain::with_hash_algo("Tilt".to_string(), 1, None, algo).unwrap();
+            chain
+                .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+                .unwrap();
+            chain.generate_new_block().unwrap();
+
+            let block = chain.get_chain().last().unwrap();
+            let hash = algo.hash(&block.header);
+            assert!(Chain::meets_difficulty(&hash, 1));
+            assert_eq!(
+                block.header.merkle,
+                Chain::get_merkle(&block.transactions, algo)
+            );
+            assert!(chain.is_valid());
+            assert_eq!(chain.get_block_by_hash(&hash), Some(block));
+        }
+    }
+
+    #[derive(Debug)]
+    struct FakeClock {
+        next: std::sync::Mutex<DateTime<Utc>>,
+        step: chrono::Duration,
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            let mut next = self.next.lock().unwrap();
+            let now = *next;
+            *next = now + self.step;
+            now
+        }
+    }
+
+    #[test]
+    fn fake_clock_advances_block_timestamps_by_fixed_delta() {
+        let start = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let step = chrono::Duration::seconds(30);
+        let clock = FakeClock {
+            next: std::sync::Mutex::new(start),
+            step,
+        };
+        let mut chain = Chain::with_clock("Tilt".to_string(), 1, None, Box::new(clock)).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.chains[0].header.timestamp, start);
+        for pair in chain.chains.windows(2) {
+            assert_eq!(pair[1].header.timestamp - pair[0].header.timestamp, step);
+        }
+    }
+
+    #[test]
+    fn parallel_mining_finds_same_nonce_as_single_thread() {
+        let template = unmined_header(2);
+        let mut single = template.clone();
+        Chain::proof_of_work(
+            &mut single,
+            DEFAULT_MAX_MINING_ITERATIONS,
+            HashAlgo::Sha256,
+            1,
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+
+        for threads in [2, 4, 7] {
+            let mut parallel = template.clone();
+            Chain::proof_of_work(
+                &mut parallel,
+                DEFAULT_MAX_MINING_ITERATIONS,
+                HashAlgo::Sha256,
+                threads,
+                &AtomicBool::new(false),
+                false,
+            )
+            .unwrap();
+            assert_eq!(parallel.nonce, single.nonce);
+            assert!(HashAlgo::Sha256.hash(&parallel).starts_with("00"));
+        }
+    }
+
+    #[test]
+    fn update_mining_threads_rejects_zero() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_mining_threads(3));
+        assert!(!chain.update_mining_threads(0));
+        assert_eq!(chain.get_mining_threads(), 3);
+    }
+
+    #[test]
+    fn cancelled_mining_appends_no_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.update_difficulty(MAX_DIFFICULTY).unwrap();
+
+        let cancel = std::sync::Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = std::sync::Arc::clone(&cancel);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                cancel.store(true, Ordering::Relaxed);
+            })
+        };
+        let result = chain.generate_new_block_cancellable(&cancel);
+        canceller.join().unwrap();
+
+        assert!(matches!(result, Err(BlockchainError::Cancelled)));
+        assert_eq!(chain.get_chain().len(), 1);
+        assert_eq!(chain.current_transactions.len(), 1);
+    }
+
+    #[test]
+    fn export_csv_writes_one_row_per_transaction() {
+        let path = temp_path("export.csv");
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain
+            .add_transaction("Bob".to_string(), "Al, \"Jr\"".to_string(), 4 * COIN, None)
+            .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 5 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain.export_csv(&path).unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("block_index,timestamp,sender,receiver,amount")
+        );
+        let rows: Vec<&str> = lines.collect();
+        let total: usize = chain.chains.iter().map(|b| b.transactions.len()).sum();
+        assert_eq!(rows.len(), total);
+        assert!(rows[0].starts_with("0,"));
+        assert!(rows[0].contains(",Root,Tilt,"));
+        assert!(
+            rows.iter()
+                .any(|row| row.contains(",Bob,\"Al, \"\"Jr\"\"\",4"))
+        );
+    }
+
+    #[test]
+    fn import_transactions_skips_invalid_entries() {
+        let path = temp_path("import.json");
+        std::fs::write(
+            &path,
+            r#"[
+                {"sender": "Tilt", "receiver": "Bob", "amount": 1000000000},
+                {"sender": "Alice", "receiver": "Bob", "amount": 500000000},
+                {"sender": "Tilt", "receiver": "Carol", "amount": 200000000, "fee": 50000000}
+            ]"#,
+        )
+        .unwrap();
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+
+        let accepted = chain.import_transactions(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(accepted, 2);
+        assert_eq!(chain.current_transactions.len(), 2);
+        assert_eq!(chain.current_transactions[1].receiver, "Carol");
+    }
+
+    #[test]
+    fn remove_pending_keeps_order_of_remaining_transactions() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        for receiver in ["Bob", "Carol", "Dave"] {
+            chain
+                .add_transaction("Tilt".to_string(), receiver.to_string(), COIN, None)
+                .unwrap();
+        }
+
+        let removed = chain.remove_pending(1).unwrap();
+        assert_eq!(removed.receiver, "Carol");
+        assert!(chain.remove_pending(2).is_none());
+
+        let receivers: Vec<&str> = chain
+            .pending_transactions()
+            .iter()
+            .map(|tx| tx.receiver.as_str())
+            .collect();
+        assert_eq!(receivers, ["Bob", "Dave"]);
+    }
+
+    #[test]
+    fn replace_chain_rejects_shorter_valid_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        let shorter = Chain::new("Bob".to_string(), 1, None).unwrap();
+
+        assert!(!chain.replace_chain(shorter.get_chain().clone()));
+        assert_eq!(chain.get_chain().len(), 2);
+    }
+
+    #[test]
+    fn replace_chain_adopts_longer_valid_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let mut longer = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        longer.generate_new_block().unwrap();
+        longer.generate_new_block().unwrap();
+
+        assert!(chain.replace_chain(longer.get_chain().clone()));
+        assert_eq!(chain.get_chain(), longer.get_chain());
+        assert_eq!(chain.pending_transactions().len(), 1);
+        assert_eq!(chain.pending_transactions()[0].receiver, "Bob");
+    }
+
+    #[test]
+    fn replace_chain_rejects_longer_invalid_chain() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let mut longer = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        longer.generate_new_block().unwrap();
+        longer.chains[1].transactions[0].amount = 1000 * COIN;
+
+        assert!(!chain.replace_chain(longer.get_chain().clone()));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn built_block_is_appended_by_submit_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+
+        let block = chain.build_block().unwrap();
+        assert_eq!(chain.get_chain().len(), 1);
+        assert_eq!(block.get_index(), 1);
+        chain.submit_block(block.clone()).unwrap();
+
+        assert_eq!(chain.get_chain().last(), Some(&block));
+        assert!(chain.pending_transactions().is_empty());
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn submit_block_rejects_wrong_previous_hash() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let mut block = chain.build_block().unwrap();
+        block.header.previous_hash = ZERO_HASH.to_string();
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::BrokenLink(1)))
+        ));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn submit_block_rejects_insufficient_work() {
+        let mut chain = Chain::new("Tilt".to_string(), 2, None).unwrap();
+        let mut block = chain.build_block().unwrap();
+        while Chain::meets_difficulty(&HashAlgo::Sha256.hash(&block.header), 2) {
+            block.header.nonce += 1;
+        }
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::WrongDifficulty(1)))
+        ));
+    }
+
+    #[test]
+    fn block_hash_covers_transaction_count() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        let original = chain.block_hash(&chain.chains[1]);
+        let header_hash = HashAlgo::Sha256.hash(&chain.chains[1].header);
+
+        chain.chains[1].count += 1;
+
+        assert_ne!(chain.block_hash(&chain.chains[1]), original);
+        assert_eq!(HashAlgo::Sha256.hash(&chain.chains[1].header), header_hash);
+        assert_eq!(chain.find_tampering(), Some(Tampering::CountMismatch(1)));
+    }
+
+    /// Layer recording the message of every event it sees.
+    #[derive(Clone, Default)]
+    struct EventMessages(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for EventMessages {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Message(Option<String>);
+            impl tracing::field::Visit for Message {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" {
+                        self.0 = Some(format!("{value:?}"));
+                    }
+                }
+            }
+            let mut message = Message(None);
+            event.record(&mut message);
+            if let Some(message) = message.0 {
+                self.0.lock().unwrap().push(message);
+            }
+        }
+    }
+
+    #[test]
+    fn mining_emits_block_mined_event() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let messages = EventMessages::default();
+        let subscriber = tracing_subscriber::registry().with(messages.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+            chain
+                .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+                .unwrap();
+            chain.generate_new_block().unwrap();
+        });
+
+        let messages = messages.0.lock().unwrap().clone();
+        assert_eq!(messages.iter().filter(|m| *m == "block mined").count(), 2);
+        assert!(messages.iter().any(|m| m == "transaction accepted"));
+    }
+
+    #[test]
+    fn quiet_mining_produces_same_block() {
+        let start = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mine = |quiet: bool| {
+            let clock = FakeClock {
+                next: std::sync::Mutex::new(start),
+                step: chrono::Duration::seconds(10),
+            };
+            let mut chain =
+                Chain::with_clock("Tilt".to_string(), 2, None, Box::new(clock)).unwrap();
+            chain.set_quiet(quiet);
+            chain.generate_new_block().unwrap();
+            chain.chains.pop().unwrap()
+        };
+
+        assert_eq!(mine(true), mine(false));
+    }
+
+    #[test]
+    fn replace_chain_prefers_more_work_over_more_blocks() {
+        let mut long = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        long.generate_new_block().unwrap();
+        long.generate_new_block().unwrap();
+        let mut heavy = Chain::new("Bob".to_string(), 3, None).unwrap();
+        heavy.generate_new_block().unwrap();
+        assert!(heavy.total_work() > long.total_work());
+
+        assert!(!heavy.replace_chain(long.get_chain().clone()));
+        assert!(long.replace_chain(heavy.get_chain().clone()));
+        assert_eq!(long.get_chain().len(), 2);
+        assert_eq!(long.total_work(), 2 * heavy.chains[1].get_header().work());
+        assert_eq!(heavy.chains[1].get_header().work(), 16u128.pow(3));
+    }
+
+    #[test]
+    fn replace_chain_rejects_blocks_breaking_chain_rules() {
+        let genesis = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let config = || {
+            ChainConfig::new("Tilt".to_string())
+                .difficulty(2)
+                .genesis_timestamp(genesis)
+        };
+        let mut chain = Chain::from_config(config()).unwrap();
+
+        let mut inflated = Chain::from_config(config()).unwrap();
+        inflated.generate_new_block().unwrap();
+        let mut candidate = inflated.chains.clone();
+        let coinbase = &mut candidate[1].transactions[0];
+        coinbase.amount += COIN;
+        coinbase.outputs[0].amount += COIN;
+        reseal(&mut candidate[1]);
+        candidate[1].header.difficulty = 2;
+        candidate[1].header.nonce = 0;
+        while !Chain::meets_difficulty(&HashAlgo::Sha256.hash(&candidate[1].header), 2) {
+            candidate[1].header.nonce += 1;
+        }
+        candidate[1].hash = HashAlgo::Sha256.hash(&candidate[1].header);
+        assert!(Chain::find_tampering_in(&candidate, HashAlgo::Sha256, &BTreeMap::new()).is_none());
+        assert!(!chain.replace_chain(candidate));
+
+        let mut easy = Chain::from_config(config()).unwrap();
+        easy.update_difficulty(1).unwrap();
+        easy.mine_n(17).unwrap();
+        assert!(easy.total_work() > chain.total_work());
+        assert!(!chain.replace_chain(easy.chains.clone()));
+        assert_eq!(chain.get_chain().len(), 1);
+
+        assert!(chain.replace_chain(inflated.chains.clone()));
+    }
+
+    #[test]
+    fn mining_continues_at_difficulty_of_adopted_chain() {
+        let mut light = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let mut heavy = Chain::new("Bob".to_string(), 2, None).unwrap();
+        heavy.generate_new_block().unwrap();
+
+        assert!(light.replace_chain(heavy.get_chain().clone()));
+        assert_eq!(light.get_difficulty(), 2);
+        light.generate_new_block().unwrap();
+        let block = light.latest().unwrap().clone();
+        assert_eq!(block.header.difficulty, 2);
+        heavy.submit_block(block).unwrap();
+        assert_eq!(heavy.get_chain(), light.get_chain());
+    }
+
+    #[test]
+    fn find_tampering_detects_header_not_meeting_difficulty() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.chains[1].header.difficulty = 8;
+
+        assert_eq!(chain.find_tampering(), Some(Tampering::WrongDifficulty(1)));
+    }
+
+    #[test]
+    fn submit_block_accepts_block_after_its_parent() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let parent = chain.chains[0].header.timestamp;
+        let block = chain
+            .build_block_at(
+                parent + chrono::Duration::seconds(1),
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+
+        chain.submit_block(block).unwrap();
+        assert_eq!(chain.get_chain().len(), 2);
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn submit_block_rejects_block_before_its_parent() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let parent = chain.chains[0].header.timestamp;
+        let block = chain
+            .build_block_at(
+                parent - chrono::Duration::seconds(1),
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(
+                Tampering::TimestampOutOfOrder(1)
+            ))
+        ));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn stored_hash_matches_header_hash_of_untampered_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        for block in chain.get_chain() {
+            assert_eq!(block.get_hash(), HashAlgo::Sha256.hash(block.get_header()));
+        }
+        assert_eq!(chain.last_hash().unwrap(), chain.chains[1].hash);
+    }
+
+    #[test]
+    fn find_tampering_detects_stale_stored_hash() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chains[1].hash = ZERO_HASH.to_string();
+        assert_eq!(chain.find_tampering(), Some(Tampering::HashMismatch(1)));
+    }
+
+    #[test]
+    fn len_and_is_empty_count_genesis() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert_eq!(chain.len(), 1);
+        assert!(!chain.is_empty());
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.len(), 2);
+    }
+
+    #[test]
+    fn height_is_index_of_latest_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert_eq!(chain.height(), 0);
+
+        chain.mine_n(2).unwrap();
+        assert_eq!(chain.height(), 2);
+        assert_eq!(chain.latest().unwrap().get_index(), chain.height());
+        assert!(chain.get_block_by_index(chain.height() + 1).is_none());
+    }
+
+    #[test]
+    fn genesis_and_latest_are_the_same_block_on_a_fresh_chain() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let genesis = chain.genesis().unwrap();
+
+        assert_eq!(genesis.get_index(), 0);
+        assert_eq!(genesis.get_header().get_previous_hash(), ZERO_HASH);
+        assert_eq!(chain.latest(), Some(genesis));
+    }
+
+    #[test]
+    fn latest_follows_newly_mined_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.latest().unwrap().get_index(), 1);
+        assert_eq!(chain.genesis().unwrap().get_index(), 0);
+    }
+
+    #[test]
+    fn transaction_display_shows_parties_amount_and_fee() {
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 21 * COIN / 2);
+        assert_eq!(transaction.to_string(), "Alice -> Bob: 10.5");
+        assert_eq!(
+            transaction.with_fee(COIN / 4).to_string(),
+            "Alice -> Bob: 10.5 (fee 0.25)"
+        );
+    }
+
+    #[test]
+    fn block_display_shows_index_short_hash_count_and_nonce() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        let block = chain.latest().unwrap();
+
+        let summary = block.to_string();
+        assert!(summary.starts_with("#1 "));
+        assert!(summary.contains(&block.hash[..SHORT_HASH_LEN]));
+        assert!(!summary.contains(&block.hash));
+        assert!(summary.contains("2 txs"));
+        assert!(summary.contains(&format!("nonce {}", block.header.nonce)));
+    }
+
+    #[test]
+    fn mine_n_appends_exactly_n_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let before = chain.len();
+
+        chain.mine_n(5).unwrap();
+        assert_eq!(chain.len(), before + 5);
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn identical_transactions_share_a_txid() {
+        let a = Transaction::new("Alice".to_string(), "Bob".to_string(), 10 * COIN);
+        let b = Transaction::new("Alice".to_string(), "Bob".to_string(), 10 * COIN);
+        assert_eq!(a.txid(HashAlgo::Sha256), b.txid(HashAlgo::Sha256));
+        assert_ne!(
+            a.txid(HashAlgo::Sha256),
+            a.with_fee(COIN).txid(HashAlgo::Sha256)
+        );
+    }
+
+    #[test]
+    fn get_transaction_by_id_finds_committed_transaction() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        let txid = chain.pending_transactions()[0].txid(HashAlgo::Sha256);
+        assert!(chain.get_transaction_by_id(&txid).is_none());
+
+        chain.generate_new_block().unwrap();
+        let found = chain.get_transaction_by_id(&txid).unwrap();
+        assert_eq!(found.receiver, "Bob");
+        assert!(chain.get_transaction_by_id(ZERO_HASH).is_none());
+    }
+
+    #[test]
+    fn add_transaction_rejects_duplicate_of_pending_transaction() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+
+        let err = chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap_err();
+        assert!(matches!(err, BlockchainError::DuplicateTransaction(_)));
+        assert_eq!(chain.pending_transactions().len(), 1);
+    }
+
+    #[test]
+    fn pop_block_keeps_genesis() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.pop_block().is_none());
+        assert_eq!(chain.len(), 1);
+    }
+
+    #[test]
+    fn pop_block_returns_tip_and_restores_its_transactions() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let work = chain.total_work();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        let tip = chain.latest().unwrap().clone();
+
+        assert_eq!(chain.pop_block(), Some(tip.clone()));
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain.total_work(), work);
+        assert_eq!(chain.pending_transactions(), &tip.transactions[1..]);
+        assert_eq!(chain.get_balance("Bob"), 0);
+        assert!(chain.is_valid());
+    }
+
+    /// Recomputes a modified block's count and Merkle root, then mines it again.
+    fn reseal(block: &mut Block) {
+        block.count = block.transactions.len() as u32;
+        block.header.merkle = Chain::get_merkle(&block.transactions, HashAlgo::Sha256);
+        Chain::proof_of_work(
+            &mut block.header,
+            DEFAULT_MAX_MINING_ITERATIONS,
+            HashAlgo::Sha256,
+            1,
+            &AtomicBool::new(false),
+            false,
+        )
+        .unwrap();
+        block.hash = HashAlgo::Sha256.hash(&block.header);
+    }
+
+    #[test]
+    fn is_coinbase_matches_coinbase_sender() {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.chains[0].transactions[0].is_coinbase());
+        assert!(!Transaction::new("Tilt".to_string(), "Bob".to_string(), COIN).is_coinbase());
+    }
+
+    #[test]
+    fn submit_block_rejects_second_coinbase() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let mut block = chain.build_block().unwrap();
+        let coinbase = block.transactions[0].clone();
+        block.transactions.push(coinbase);
+        reseal(&mut block);
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::MisplacedCoinbase(
+                1
+            )))
+        ));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn submit_block_rejects_wrong_coinbase_amount() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let mut block = chain.build_block().unwrap();
+        block.transactions[0].amount += 1;
+        reseal(&mut block);
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(
+                Tampering::WrongCoinbaseAmount(1)
+            ))
+        ));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn find_tampering_detects_block_without_leading_coinbase() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain.chains[1].transactions.swap(0, 1);
+        reseal(&mut chain.chains[1]);
+
+        assert_eq!(
+            chain.find_tampering(),
+            Some(Tampering::MisplacedCoinbase(1))
+        );
+    }
+
+    #[test]
+    fn balance_at_counts_blocks_up_to_height() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(2).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 30 * COIN, None)
+            .unwrap();
+        chain.mine_n(2).unwrap();
+
+        assert_eq!(chain.balance_at("Tilt", 0), 50 * COIN);
+        assert_eq!(chain.balance_at("Tilt", 2), 150 * COIN);
+        assert_eq!(chain.balance_at("Tilt", 3), 170 * COIN);
+        assert_eq!(chain.balance_at("Bob", 2), 0);
+        assert_eq!(chain.balance_at("Bob", 3), 30 * COIN);
+        assert_eq!(
+            chain.balance_at("Tilt", u64::MAX),
+            chain.get_balance("Tilt")
+        );
+    }
+
+    #[test]
+    fn locked_transaction_waits_for_a_later_block() {
+        let start = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let clock = FakeClock {
+            next: std::sync::Mutex::new(start),
+            step: chrono::Duration::seconds(60),
+        };
+        let mut chain = Chain::with_clock("Tilt".to_string(), 1, None, Box::new(clock)).unwrap();
+        let locked = Transaction::new("Tilt".to_string(), "Bob".to_string(), COIN)
+            .with_lock_until(start + chrono::Duration::seconds(90));
+        chain.submit_transaction(locked.clone()).unwrap();
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.chains[1].transactions.len(), 1);
+        assert_eq!(chain.current_transactions, vec![locked.clone()]);
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.chains[2].transactions[1], locked);
+        assert!(chain.current_transactions.is_empty());
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn memo_changes_transaction_hash() {
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), COIN);
+        let first = transaction.clone().with_memo("rent".to_string());
+        let second = transaction.with_memo("gift".to_string());
+
+        assert_ne!(first.txid(HashAlgo::Sha256), second.txid(HashAlgo::Sha256));
+        assert_ne!(
+            Chain::get_merkle(&[first], HashAlgo::Sha256),
+            Chain::get_merkle(&[second], HashAlgo::Sha256)
+        );
+    }
+
+    #[test]
+    fn submit_transaction_rejects_over_length_memo() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let transaction = Transaction::new("Tilt".to_string(), "Bob".to_string(), COIN);
+
+        let err = chain
+            .submit_transaction(transaction.clone().with_memo("x".repeat(MAX_MEMO_LEN + 1)))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BlockchainError::MemoTooLong {
+                len: 257,
+                max: MAX_MEMO_LEN
+            }
+        ));
+        chain
+            .submit_transaction(transaction.with_memo("x".repeat(MAX_MEMO_LEN)))
+            .unwrap();
+    }
+
+    #[test]
+    fn submit_block_rejects_block_missing_compact_target() {
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).target_bits(0x2100_ffff))
+                .unwrap();
+        let mut block = chain.build_block().unwrap();
+        block.header.bits = None;
+        reseal(&mut block);
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::WrongDifficulty(1)))
+        ));
+    }
+
+    #[test]
+    fn header_with_five_zero_bits_meets_bit_difficulty_five_only() {
+        let mut header = unmined_header(5);
+        header.unit = DifficultyUnit::Bits;
+        while HashAlgo::Sha256.digest_item(&header)[0] >> 2 != 1 {
+            header.nonce += 1;
+        }
+        let digest = HashAlgo::Sha256.digest_item(&header);
+
+        assert!(header.target().unwrap().is_met_by(&digest));
+        header.difficulty = 6;
+        assert!(!header.target().unwrap().is_met_by(&digest));
+    }
+
+    /// Asserts that two chains hold the same blocks, pending transactions, and
+    /// settings.
+    fn assert_same_chain(left: &Chain, right: &Chain) {
+        assert_eq!(left.chains, right.chains);
+        assert_eq!(left.current_transactions, right.current_transactions);
+        assert_eq!(left.difficulty, right.difficulty);
+        assert_eq!(left.miner_address, right.miner_address);
+        assert_eq!(left.reward, right.reward);
+        assert_eq!(left.halving_interval, right.halving_interval);
+        assert_eq!(left.coinbase_maturity, right.coinbase_maturity);
+        assert_eq!(left.reward_split, right.reward_split);
+        assert_eq!(left.retarget_interval, right.retarget_interval);
+        assert_eq!(left.target_block_time, right.target_block_time);
+        assert_eq!(left.max_mempool, right.max_mempool);
+        assert_eq!(left.max_block_txs, right.max_block_txs);
+        assert_eq!(left.max_mining_iterations, right.max_mining_iterations);
+        assert_eq!(left.hash_algo, right.hash_algo);
+        assert_eq!(left.target_bits, right.target_bits);
+        assert_eq!(left.difficulty_unit, right.difficulty_unit);
+        assert_eq!(left.total_work, right.total_work);
+    }
+
+    fn sample_chain() -> Chain {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, Some(COIN))
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        chain
+            .submit_transaction(
+                Transaction::new("Tilt".to_string(), "Carol".to_string(), COIN)
+                    .with_memo("rent".to_string())
+                    .with_lock_until(Utc::now()),
+            )
+            .unwrap();
+        chain
+    }
+
+    #[test]
+    fn save_bin_round_trips_chain() {
+        let path = temp_path("chain.bin");
+        let chain = sample_chain();
+
+        chain.save_bin(&path).unwrap();
+        let loaded = Chain::load_bin(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_same_chain(&loaded, &chain);
+    }
+
+    #[test]
+    fn json_and_bin_loads_yield_identical_chains() {
+        let json_path = temp_path("same.json");
+        let bin_path = temp_path("same.bin");
+        let chain = sample_chain();
+
+        chain.save_to_file(&json_path).unwrap();
+        chain.save_bin(&bin_path).unwrap();
+        let from_json = Chain::load_from_file(&json_path).unwrap();
+        let from_bin = Chain::load_bin(&bin_path).unwrap();
+        let json_len = std::fs::metadata(&json_path).unwrap().len();
+        let bin_len = std::fs::metadata(&bin_path).unwrap().len();
+        std::fs::remove_file(&json_path).unwrap();
+        std::fs::remove_file(&bin_path).unwrap();
+
+        assert_same_chain(&from_json, &from_bin);
+        assert!(bin_len < json_len);
+    }
+
+    #[test]
+    fn on_block_hook_fires_once_per_mined_block() {
+        let mined = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let seen = std::sync::Arc::clone(&mined);
+        chain.set_on_block(Box::new(move |block| {
+            seen.lock().unwrap().push(block.get_index());
+        }));
+
+        chain.mine_n(3).unwrap();
+        let block = chain.build_block().unwrap();
+        chain.submit_block(block).unwrap();
+
+        assert_eq!(*mined.lock().unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn get_merkle_matches_sequential_root_over_many_transactions() {
+        let transactions: Vec<_> = (1..=1000)
+            .map(|amount| Transaction::new("Alice".to_string(), "Bob".to_string(), amount))
+            .collect();
+
+        let mut level: Vec<_> = transactions
+            .iter()
+            .map(|t| HashAlgo::Sha256.digest_item(t))
+            .collect();
+        while level.len() > 1 {
+            if level.len() % 2 == 1 {
+                level.push(level[level.len() - 1]);
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| Chain::merkle_parent(&pair[0], &pair[1], HashAlgo::Sha256))
+                .collect();
+        }
+
+        assert_eq!(
+            Chain::get_merkle(&transactions, HashAlgo::Sha256),
+            Chain::hex_to_string(&level[0])
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_chain_without_genesis() {
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&serde_json::to_vec(&sample_chain().snapshot()).unwrap())
+                .unwrap();
+        json["chains"] = serde_json::Value::Array(Vec::new());
+
+        let err = Chain::from_json(json.to_string().as_bytes()).unwrap_err();
+        assert!(matches!(err, BlockchainError::InvalidChain));
+    }
+
+    #[test]
+    fn from_bin_rejects_truncated_bytes() {
+        let mut bytes = BIN_MAGIC.to_vec();
+        bincode::serde::encode_into_std_write(
+            sample_chain().snapshot(),
+            &mut bytes,
+            bincode::config::standard(),
+        )
+        .unwrap();
+
+        let err = Chain::from_bin(&bytes[..bytes.len() / 2]).unwrap_err();
+        assert!(matches!(err, BlockchainError::BinaryDecode(_)));
+        assert!(Chain::from_bin(&bytes).is_ok());
+    }
+
+    #[test]
+    fn checked_addresses_accept_valid_and_reject_mutated_address() {
+        let miner = Wallet::new();
+        let bob = Wallet::new().checked_address();
+        let mut chain =
+            Chain::from_config(ChainConfig::new(miner.checked_address()).check_addresses(true))
+                .unwrap();
+        chain
+            .add_transaction(miner.checked_address(), bob.clone(), COIN, None)
+            .unwrap();
+
+        let mut typo = bob.into_bytes();
+        typo[5] = if typo[5] == b'2' { b'3' } else { b'2' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(matches!(
+            chain.add_transaction(miner.checked_address(), typo.clone(), COIN, None),
+            Err(BlockchainError::InvalidAddress(address)) if address == typo
+        ));
+        assert!(matches!(
+            Chain::from_config(ChainConfig::new(typo).check_addresses(true)),
+            Err(BlockchainError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn checked_address_signs_transactions() {
+        let wallet = Wallet::new();
+        let mut chain = Chain::new(wallet.checked_address(), 1, None).unwrap();
+        let mut transaction = Transaction::new(wallet.checked_address(), "Bob".to_string(), COIN);
+        transaction.sign(&wallet).unwrap();
+
+        chain.add_signed_transaction(transaction).unwrap();
+    }
+
+    #[test]
+    fn generate_new_block_to_pays_each_miner_one_reward() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.generate_new_block_to("Alice").unwrap();
+        chain.generate_new_block_to("Bob").unwrap();
+
+        assert_eq!(chain.get_balance("Alice"), chain.get_reward());
+        assert_eq!(chain.get_balance("Bob"), chain.get_reward());
+        assert_eq!(chain.get_balance("Tilt"), chain.get_reward());
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn median_time_past_uses_last_eleven_blocks() {
+        let start = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let clock = FakeClock {
+            next: std::sync::Mutex::new(start),
+            step: chrono::Duration::seconds(60),
+        };
+        let mut chain = Chain::with_clock("Tilt".to_string(), 1, None, Box::new(clock)).unwrap();
+        assert_eq!(chain.median_time_past(), start);
+
+        chain.mine_n(12).unwrap();
+        // Blocks 2 to 12 are the last eleven; block 7 is their median.
+        assert_eq!(chain.median_time_past(), chain.chains[7].header.timestamp);
+    }
+
+    #[test]
+    fn submit_block_rejects_block_at_median_time_past() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let block = chain
+            .build_block_at(chain.median_time_past(), &AtomicBool::new(false))
+            .unwrap();
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::TimeTooOld(1)))
+        ));
+        assert_eq!(chain.get_chain().len(), 1);
+    }
+
+    #[test]
+    fn submit_block_rejects_far_future_block() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let block = chain
+            .build_block_at(
+                Utc::now() + chrono::Duration::hours(3),
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::TimeTooNew(1)))
+        ));
+        let block = chain
+            .build_block_at(
+                Utc::now() + chrono::Duration::hours(1),
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+        chain.submit_block(block).unwrap();
+    }
+
+    /// Returns a chain holding a pending payment, and a valid candidate block
+    /// confirming it.
+    fn chain_and_candidate() -> (Chain, Block) {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, Some(COIN / 10))
+            .unwrap();
+        let block = chain.build_block().unwrap();
+        (chain, block)
+    }
+
+    #[test]
+    fn verify_block_accepts_candidate_without_appending_it() {
+        let (chain, block) = chain_and_candidate();
+        chain.verify_block(&block).unwrap();
+        assert_eq!(chain.len(), 1);
+    }
+
+    #[test]
+    fn verify_block_rejects_wrong_previous_hash() {
+        let (chain, mut block) = chain_and_candidate();
+        block.header.previous_hash = ZERO_HASH.to_string();
+        reseal(&mut block);
+
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::BrokenLink(1)))
+        ));
+    }
+
+    #[test]
+    fn verify_block_rejects_unmet_target() {
+        let (chain, mut block) = chain_and_candidate();
+        while block.hash.starts_with('0') {
+            block.header.nonce += 1;
+            block.hash = HashAlgo::Sha256.hash(&block.header);
         }
-        s
+
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::WrongDifficulty(1)))
+        ));
     }
 
-    /// Gets the hash of the last block in the chain.
-    ///
-    /// # Returns
-    ///
-    /// Hash of the last block, or a string of zeros if the chain is empty
-    pub(crate) fn last_hash(&self) -> String {
-        let Some(block) = self.chains.last() else {
-            return String::from_utf8(vec![48; 64]).unwrap();
-        };
-        Self::hash(&block.header)
+    #[test]
+    fn verify_block_rejects_merkle_mismatch() {
+        let (chain, mut block) = chain_and_candidate();
+        block.transactions[1].amount += 1;
+
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::MerkleMismatch(1)))
+        ));
     }
 
-    /// Updates the mining difficulty.
-    ///
-    /// # Arguments
-    ///
-    /// * `new_difficulty` - New mining difficulty value
-    ///
-    /// # Returns
-    ///
-    /// `true` if the difficulty was successfully updated
-    pub(crate) const fn update_difficulty(&mut self, new_difficulty: u32) -> bool {
-        self.difficulty = new_difficulty;
-        true
+    #[test]
+    fn verify_block_rejects_count_mismatch() {
+        let (chain, mut block) = chain_and_candidate();
+        assert_eq!(block.get_count(), 2);
+        block.count += 1;
+
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::CountMismatch(1)))
+        ));
     }
 
-    /// Updates the mining reward amount.
-    ///
-    /// # Arguments
-    ///
-    /// * `new_reward` - New mining reward value
-    ///
-    /// # Returns
-    ///
-    /// `true` if the reward was successfully updated
-    pub(crate) const fn update_reward(&mut self, new_reward: f32) -> bool {
-        self.reward = new_reward;
-        true
+    #[test]
+    fn verify_block_rejects_malformed_coinbase() {
+        let (chain, mut block) = chain_and_candidate();
+        let coinbase = block.transactions[0].clone();
+        block.transactions.push(coinbase);
+        reseal(&mut block);
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::MisplacedCoinbase(
+                1
+            )))
+        ));
+
+        let (chain, mut block) = chain_and_candidate();
+        block.transactions[0].amount += 1;
+        reseal(&mut block);
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(
+                Tampering::WrongCoinbaseAmount(1)
+            ))
+        ));
     }
 
-    /// Creates and mines a new block containing pending transactions.
-    ///
-    /// Includes a mining reward transaction and performs proof-of-work.
-    ///
-    /// # Returns
-    ///
-    /// `true` if the block was successfully generated and added to the chain
-    pub(crate) fn generate_new_block(&mut self) -> bool {
-        let header = BlockHeader {
-            timestamp: Utc::now(),
-            nonce: 0,
-            previous_hash: self.last_hash(),
-            merkle: String::new(),
-            difficulty: self.difficulty,
-        };
+    #[test]
+    fn prune_drops_old_transactions_and_keeps_headers() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, Some(COIN))
+            .unwrap();
+        chain.mine_n(4).unwrap();
+        let headers: Vec<_> = chain.chains.iter().map(|b| b.header.clone()).collect();
+        let balances = (chain.get_balance("Tilt"), chain.get_balance("Bob"));
+        let supply = chain.total_supply();
 
-        let reward_transaction = Transaction {
-            sender: String::from("Root"),
-            receiver: self.miner_address.clone(),
-            amount: self.reward,
-        };
+        chain.prune(2);
 
-        let mut block = Block {
-            header,
-            count: 0,
-            transactions: Vec::new(),
-        };
+        assert_eq!(chain.len(), 5);
+        for block in &chain.chains[..3] {
+            assert!(block.is_pruned());
+            assert!(block.get_transactions().is_empty());
+        }
+        for block in &chain.chains[3..] {
+            assert!(!block.is_pruned());
+            assert!(!block.get_transactions().is_empty());
+        }
+        assert_eq!(chain.chains[1].to_string().matches("pruned").count(), 1);
+        let after: Vec<_> = chain.chains.iter().map(|b| b.header.clone()).collect();
+        assert_eq!(after, headers);
+        assert!(chain.is_valid());
+        assert_eq!(
+            (chain.get_balance("Tilt"), chain.get_balance("Bob")),
+            balances
+        );
+        assert_eq!(chain.total_supply(), supply);
 
-        block.transactions.push(reward_transaction);
-        block.transactions.append(&mut self.current_transactions);
-        block.count = block.transactions.len() as u32;
-        block.header.merkle = Self::get_merkle(&block.transactions.clone());
-        Self::proof_of_work(&mut block.header);
+        chain.generate_new_block().unwrap();
+        assert!(chain.is_valid());
+    }
 
-        println!("Last {:#?}", &block);
-        self.chains.push(block);
-        true
+    #[test]
+    fn pruned_chain_survives_save_and_load() {
+        let path = temp_path("pruned.json");
+        let mut chain = sample_chain();
+        chain.mine_n(2).unwrap();
+        chain.prune(1);
+
+        chain.save_to_file(&path).unwrap();
+        let loaded = Chain::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.chains[1].is_pruned());
+        assert_eq!(loaded.get_balance("Bob"), chain.get_balance("Bob"));
+        assert_eq!(loaded.get_balance("Tilt"), chain.get_balance("Tilt"));
     }
 
-    /// Calculates the Merkle root of a set of transactions.
-    ///
-    /// # Arguments
-    ///
-    /// * `transactions` - List of transactions to include in the Merkle tree
-    ///
-    /// # Returns
-    ///
-    /// Merkle root hash as a string
-    pub(crate) fn get_merkle(transactions: &[Transaction]) -> String {
-        let mut merkle = Vec::new();
+    #[test]
+    fn pop_block_keeps_pruned_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(2).unwrap();
+        chain.prune(0);
 
-        for t in transactions {
-            let hash = Self::hash(t);
-            merkle.push(hash);
-        }
+        assert_eq!(chain.pop_block(), None);
+        assert_eq!(chain.len(), 3);
+    }
 
-        if merkle.len() % 2 == 1 {
-            let last = merkle.last().cloned().unwrap();
-            merkle.push(last);
-        }
+    #[test]
+    fn checkpoint_trusts_bodies_below_it_but_not_hashes() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(5).unwrap();
+        chain.add_checkpoint(3, chain.chains[3].hash.clone());
+
+        // Bodies below the checkpoint are not re-verified.
+        chain.chains[1].transactions[0].amount += 1;
+        assert_eq!(chain.find_tampering(), None);
+
+        let hash = std::mem::replace(&mut chain.chains[2].hash, ZERO_HASH.to_string());
+        assert_eq!(chain.find_tampering(), Some(Tampering::HashMismatch(2)));
+        chain.chains[2].hash = hash;
+
+        chain.add_checkpoint(2, ZERO_HASH.to_string());
+        assert_eq!(
+            chain.find_tampering(),
+            Some(Tampering::CheckpointMismatch(2))
+        );
+    }
+
+    #[test]
+    fn blocks_above_checkpoint_are_fully_validated() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(5).unwrap();
+        chain.add_checkpoint(3, chain.chains[3].hash.clone());
 
-        while merkle.len() > 1 {
-            let mut h1 = merkle.remove(0);
-            let h2 = merkle.remove(0);
-            h1.push_str(&h2);
-            let hn = Self::hash(&h1);
-            merkle.push(hn);
+        chain.chains[4].transactions[0].amount += 1;
+        assert_eq!(chain.find_tampering(), Some(Tampering::MerkleMismatch(4)));
+    }
+
+    #[test]
+    fn reorg_returns_orphaned_transactions_to_mempool() {
+        let genesis = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut chain =
+            Chain::with_genesis_timestamp("Tilt".to_string(), 1, None, genesis).unwrap();
+        let mut fork = Chain::with_genesis_timestamp("Tilt".to_string(), 1, None, genesis).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        for node in [&mut chain, &mut fork] {
+            node.add_transaction("Tilt".to_string(), "Carol".to_string(), 2 * COIN, None)
+                .unwrap();
         }
+        chain.generate_new_block().unwrap();
+        fork.mine_n(2).unwrap();
 
-        merkle.pop().unwrap()
+        assert!(chain.replace_chain(fork.get_chain().clone()));
+        // Carol's payment is confirmed by the fork; Bob's only lived in the
+        // orphaned block.
+        let pending = chain.pending_transactions();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].receiver, "Bob");
+        assert_eq!(chain.get_balance("Carol"), 2 * COIN);
+        assert_eq!(chain.get_balance("Bob"), 0);
     }
 
-    /// Performs proof-of-work mining on a block header.
-    ///
-    /// Repeatedly hashes the header with different nonce values until
-    /// finding a hash with the required number of leading zeros.
-    ///
-    /// # Arguments
+    /// Storage keeping its blocks in a vector shared with the test.
     ///
-    /// * `header` - Block header to mine
-    pub(crate) fn proof_of_work(header: &mut BlockHeader) {
-        let difficulty = u64::from(header.difficulty);
-        let pb = indicatif::ProgressBar::new(100);
-        pb.set_style(
-            indicatif::ProgressStyle::default_bar()
-                .template(
-                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {percent}% ({eta})",
-                )
-                .unwrap(),
-        );
-        let delta = 8 / difficulty;
-        let handle = std::thread::spawn(move || {
-            for _ in 0..(1024 / (delta)) {
-                pb.inc(delta);
-                std::thread::sleep(std::time::Duration::from_millis(difficulty * 10));
+    /// Once `limit` is set, pushing a block at or above it fails as a full disk
+    /// would.
+    #[derive(Debug, Clone)]
+    struct SharedStorage {
+        blocks: std::sync::Arc<std::sync::Mutex<Vec<Block>>>,
+        limit: std::sync::Arc<AtomicU64>,
+    }
+
+    impl Default for SharedStorage {
+        fn default() -> Self {
+            Self {
+                blocks: std::sync::Arc::default(),
+                limit: std::sync::Arc::new(u64::MAX.into()),
             }
-            pb.finish_with_message("Mining complete!");
-        });
-        let m;
-        loop {
-            let hash = Self::hash(&header);
-            let slice = &hash[..header.difficulty as usize];
-            if let Ok(val) = slice.parse::<u32>() {
-                if val != 0 {
-                    header.nonce += 1;
-                } else {
-                    m = hash;
-                    break;
-                }
-            } else {
-                header.nonce += 1;
+        }
+    }
+
+    impl SharedStorage {
+        fn set_limit(&self, limit: u64) {
+            self.limit.store(limit, Ordering::Relaxed);
+        }
+    }
+
+    impl Storage for SharedStorage {
+        fn push(&mut self, block: &Block) -> Result<(), BlockchainError> {
+            if block.index >= self.limit.load(Ordering::Relaxed) {
+                return Err(std::io::Error::from(std::io::ErrorKind::StorageFull).into());
             }
+            self.truncate(block.index)?;
+            self.blocks.lock().unwrap().push(block.clone());
+            Ok(())
+        }
+
+        fn truncate(&mut self, len: u64) -> Result<(), BlockchainError> {
+            self.blocks.lock().unwrap().truncate(len as usize);
+            Ok(())
+        }
+
+        fn load(&self) -> Result<Vec<Block>, BlockchainError> {
+            Ok(self.blocks.lock().unwrap().clone())
         }
-        handle.join().unwrap();
-        println!("Block hashed: {m}");
     }
 
-    /// Returns the JSON representation of the latest block.
-    ///
-    /// # Returns
-    ///
-    /// Pretty-printed JSON string of the latest block, or None if the chain is empty
-    pub(crate) fn get_latest_block_json(&self) -> Option<String> {
-        self.chains
-            .last()
-            .map(|b| serde_json::to_string_pretty(b).unwrap())
+    #[test]
+    fn failed_replacement_write_restores_stored_chain() {
+        let genesis = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let config = || ChainConfig::new("Tilt".to_string()).genesis_timestamp(genesis);
+        let storage = SharedStorage::default();
+        let mut chain = Chain::from_config(config().storage(Box::new(storage.clone()))).unwrap();
+        chain.generate_new_block().unwrap();
+        let mut fork = Chain::from_config(config()).unwrap();
+        fork.mine_n(3).unwrap();
+
+        storage.set_limit(3);
+        assert!(!chain.replace_chain(fork.chains.clone()));
+        assert_eq!(storage.load().unwrap(), chain.chains);
+        assert_eq!(chain.get_chain().len(), 2);
+
+        storage.set_limit(u64::MAX);
+        assert!(chain.replace_chain(fork.chains.clone()));
+        assert_eq!(storage.load().unwrap(), fork.chains);
     }
 
-    /// Returns JSON representations of all blocks in the chain.
-    ///
-    /// # Returns
-    ///
-    /// Vector of pretty-printed JSON strings for each block
-    pub(crate) fn get_blocks_json(&self) -> Vec<String> {
-        self.chains
-            .iter()
-            .map(|b| serde_json::to_string_pretty(b).unwrap())
-            .collect()
+    #[test]
+    fn failed_storage_write_leaves_chain_unchanged() {
+        let storage = SharedStorage::default();
+        let mut chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string()).storage(Box::new(storage.clone())),
+        )
+        .unwrap();
+        chain.generate_new_block().unwrap();
+        assert!(chain.update_retarget(2, Duration::from_hours(1)));
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        let block = chain.build_block().unwrap();
+        assert_ne!(chain.next_difficulty(), chain.get_difficulty());
+        let difficulty = chain.get_difficulty();
+
+        storage.set_limit(2);
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::Io(_))
+        ));
+        assert_eq!(chain.get_difficulty(), difficulty);
+        assert_eq!(chain.pending_transactions().len(), 1);
+        assert_eq!(chain.get_chain().len(), 2);
+        assert_eq!(storage.load().unwrap(), chain.chains);
     }
 
-    /// Returns the current mining difficulty.
-    pub(crate) const fn get_difficulty(&self) -> u32 {
-        self.difficulty
+    #[test]
+    fn rejected_replacement_leaves_storage_unchanged() {
+        let genesis = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let config = || ChainConfig::new("Tilt".to_string()).genesis_timestamp(genesis);
+        let storage = SharedStorage::default();
+        let mut chain = Chain::from_config(config().storage(Box::new(storage.clone()))).unwrap();
+        chain.generate_new_block().unwrap();
+        let stored = storage.load().unwrap();
+        assert_eq!(stored, chain.chains);
+
+        let mut fork = Chain::from_config(config()).unwrap();
+        fork.mine_n(3).unwrap();
+        let mut candidate = fork.chains.clone();
+        let missing = OutPoint {
+            txid: ZERO_HASH.to_string(),
+            index: 0,
+        };
+        candidate[3].transactions.push(Transaction::spend(
+            "Tilt".to_string(),
+            vec![missing],
+            vec![TxOut {
+                address: "Bob".to_string(),
+                amount: COIN,
+                script: None,
+            }],
+        ));
+        reseal(&mut candidate[3]);
+
+        assert!(!chain.replace_chain(candidate));
+        assert_eq!(storage.load().unwrap(), stored);
+        assert_eq!(chain.chains, stored);
     }
 
-    /// Returns the current mining reward.
-    pub(crate) const fn get_reward(&self) -> f32 {
-        self.reward
+    #[test]
+    fn difficulty_zero_mines_instantly_at_first_nonce() {
+        let mut chain = Chain::new("Tilt".to_string(), 0, None).unwrap();
+        chain.mine_n(10).unwrap();
+
+        assert_eq!(chain.len(), 11);
+        assert!(chain.chains.iter().all(|block| block.header.nonce == 0));
+        assert!(chain.is_valid());
     }
 
-    /// Returns a reference to the blockchain.
-    pub(crate) const fn get_chain(&self) -> &Vec<Block> {
-        &self.chains
+    #[test]
+    fn mining_stats_record_attempts_of_mined_blocks() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(2).unwrap();
+
+        let stats = chain.mining_stats();
+        assert_eq!(
+            stats.iter().map(|&(index, _, _)| index).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        for &(index, _, attempts) in &stats {
+            let nonce = chain.chains[index as usize].header.nonce;
+            assert!(attempts > nonce, "{attempts} hashes for nonce {nonce}");
+        }
+
+        chain.pop_block().unwrap();
+        assert_eq!(chain.mining_stats().len(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn failed_save_before_rename_keeps_original_file() {
+        let path = temp_path("atomic-save.json");
+        let mut chain = sample_chain();
+        chain.save_to_file(&path).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+
+        chain.generate_new_block().unwrap();
+        let json = serde_json::to_vec_pretty(&chain.snapshot()).unwrap();
+        let err = write_atomic(&path, &json, || {
+            Err(std::io::Error::other("simulated crash"))
+        })
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "simulated crash");
+        assert_eq!(std::fs::read(&path).unwrap(), saved);
+        assert!(!temp_path("atomic-save.json.tmp").exists());
+        assert_eq!(Chain::load_from_file(&path).unwrap().len(), chain.len() - 1);
+
+        chain.save_to_file(&path).unwrap();
+        assert_eq!(Chain::load_from_file(&path).unwrap().len(), chain.len());
+        std::fs::remove_file(&path).unwrap();
+    }
 
     #[test]
-    fn new_chain_has_genesis_block() {
-        let chain = Chain::new("Tilt".to_string(), 1, None);
-        assert_eq!(chain.get_chain().len(), 1);
+    fn chain_files_record_their_format_version() {
+        let chain = sample_chain();
+        let json = serde_json::to_string(&chain.snapshot()).unwrap();
+        let tag = format!("\"format_version\":{FORMAT_VERSION},");
+        assert!(json.starts_with(&format!("{{{tag}")));
+
+        let later = json.replacen(
+            &tag,
+            &format!("\"format_version\":{},", FORMAT_VERSION + 1),
+            1,
+        );
+        assert!(matches!(
+            Chain::from_json(later.as_bytes()),
+            Err(BlockchainError::UnsupportedVersion(v)) if v == FORMAT_VERSION + 1
+        ));
+        let untagged_json = json.replacen(&tag, "", 1);
+        assert!(Chain::from_json(untagged_json.as_bytes()).is_ok());
+
+        let untagged =
+            bincode::serde::encode_to_vec(chain.snapshot(), bincode::config::standard()).unwrap();
+        assert!(matches!(
+            Chain::from_bin(&untagged),
+            Err(BlockchainError::UnsupportedVersion(0))
+        ));
     }
 
     #[test]
-    fn new_chain_uses_default_reward_when_not_provided() {
-        let chain = Chain::new("Tilt".to_string(), 1, None);
-        assert!((chain.get_reward() - 50.0).abs() < f32::EPSILON);
+    fn version_1_file_is_migrated_on_load() {
+        let v1 = include_bytes!("../tests/fixtures/chain-v1.json");
+        let chain = Chain::from_json(v1).unwrap();
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain.get_balance("Bob"), 10 * COIN);
+        assert_eq!(chain.pending_transactions().len(), 1);
+        let saved = serde_json::to_value(chain.snapshot()).unwrap();
+        assert_eq!(saved["format_version"], FORMAT_VERSION);
+        assert!(saved.get("version").is_none());
+
+        let mut v0 = serde_json::from_slice::<serde_json::Value>(v1).unwrap();
+        v0["version"] = 0.into();
+        let err = Chain::from_json(v0.to_string().as_bytes()).unwrap_err();
+        assert!(matches!(err, BlockchainError::UnsupportedVersion(0)));
+        assert!(err.to_string().contains("version 0"), "{err}");
+    }
+
+    /// Returns a chain whose miner Tilt owns only the genesis coinbase output,
+    /// and that output.
+    fn funded_utxo_chain() -> (Chain, OutPoint) {
+        let chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let (coin, output) = chain.unspent_outputs("Tilt").remove(0);
+        assert_eq!(output.amount, 50 * COIN);
+        (chain, coin)
     }
 
     #[test]
-    fn new_chain_uses_custom_reward_when_provided() {
-        let chain = Chain::new("Tilt".to_string(), 1, Some(100.0));
-        assert!((chain.get_reward() - 100.0).abs() < f32::EPSILON);
+    fn spend_with_change_creates_new_outputs() {
+        let (mut chain, coin) = funded_utxo_chain();
+        let pay = |change| {
+            Transaction::spend(
+                "Tilt".to_string(),
+                vec![coin.clone()],
+                vec![
+                    TxOut {
+                        address: "Bob".to_string(),
+                        amount: 30 * COIN,
+                        script: None,
+                    },
+                    TxOut {
+                        address: "Tilt".to_string(),
+                        amount: change,
+                        script: None,
+                    },
+                ],
+            )
+            .with_fee(COIN)
+        };
+        assert!(matches!(
+            chain.submit_transaction(pay(10 * COIN)),
+            Err(BlockchainError::UnbalancedTransaction { inputs, required })
+                if inputs == 50 * COIN && required == 41 * COIN
+        ));
+
+        let spend = pay(19 * COIN);
+        let txid = spend.txid(chain.get_hash_algo());
+        chain.submit_transaction(spend).unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert!(!chain.utxos().contains_key(&coin));
+        let output = |index| OutPoint {
+            txid: txid.clone(),
+            index,
+        };
+        assert_eq!(
+            chain.unspent_outputs("Bob"),
+            vec![(
+                output(0),
+                TxOut {
+                    address: "Bob".to_string(),
+                    amount: 30 * COIN,
+                    script: None,
+                }
+            )]
+        );
+        assert_eq!(chain.utxos()[&output(1)].amount, 19 * COIN);
+        assert_eq!(chain.unspent_outputs("Tilt").len(), 2);
+        assert_eq!(chain.get_balance("Bob"), 30 * COIN);
+        assert_eq!(chain.get_balance("Tilt"), 19 * COIN + 51 * COIN);
+        assert!(chain.is_valid());
     }
 
     #[test]
-    fn transaction_added_successfully() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        let result = chain.add_transaction("Alice".to_string(), "Bob".to_string(), 10.0);
-        assert!(result);
-        assert_eq!(chain.current_transactions.len(), 1);
+    fn double_spend_is_rejected_in_mempool_and_blocks() {
+        let (mut chain, coin) = funded_utxo_chain();
+        let pay = |receiver: &str| {
+            Transaction::spend(
+                "Tilt".to_string(),
+                vec![coin.clone()],
+                vec![TxOut {
+                    address: receiver.to_string(),
+                    amount: 50 * COIN,
+                    script: None,
+                }],
+            )
+        };
+        chain.submit_transaction(pay("Bob")).unwrap();
+        assert!(matches!(
+            chain.submit_transaction(pay("Carol")),
+            Err(BlockchainError::ReplacementFeeTooLow { .. })
+        ));
+        chain.generate_new_block().unwrap();
+        assert!(matches!(
+            chain.submit_transaction(pay("Carol")),
+            Err(BlockchainError::UnspendableOutput(_))
+        ));
+
+        let mut block = chain.build_block().unwrap();
+        block.transactions.push(pay("Carol"));
+        reseal(&mut block);
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::InvalidSpend(2)))
+        ));
+        assert_eq!(chain.get_balance("Carol"), 0);
+        assert!(chain.is_valid());
     }
 
     #[test]
-    fn block_generation_includes_pending_transactions() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        chain.add_transaction("Alice".to_string(), "Bob".to_string(), 10.0);
-        chain.add_transaction("Bob".to_string(), "Alice".to_string(), 20.0);
+    fn block_includes_only_sends_the_sender_can_cover_together() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Alice".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        let send =
+            |receiver: &str| Transaction::new("Alice".to_string(), receiver.to_string(), 8 * COIN);
+        chain.submit_transaction(send("Bob")).unwrap();
+        assert!(matches!(
+            chain.submit_transaction(send("Carol")),
+            Err(BlockchainError::InsufficientFunds { available, .. }) if available == 2 * COIN
+        ));
 
-        let initial_chain_len = chain.get_chain().len();
-        chain.generate_new_block();
+        // A pool filled before the balance dropped can still hold both.
+        chain.current_transactions.push(send("Carol"));
+        chain.generate_new_block().unwrap();
 
-        // The new block should contain the two transactions plus the reward transaction
-        assert_eq!(chain.get_chain().len(), initial_chain_len + 1);
-        assert_eq!(chain.get_chain().last().unwrap().count, 3);
-        // Current transactions should be cleared
-        assert_eq!(chain.current_transactions.len(), 0);
+        let included = chain.latest().unwrap().get_transactions();
+        assert_eq!(included.len(), 2);
+        assert_eq!(included[1].receiver, "Bob");
+        assert_eq!(chain.pending_transactions(), [send("Carol")]);
+        assert_eq!(chain.get_balance("Alice"), 2 * COIN);
+        assert_eq!(chain.get_balance("Carol"), 0);
     }
 
     #[test]
-    fn hash_produces_consistent_output_for_same_input() {
-        let transaction = Transaction {
-            sender: "Alice".to_string(),
-            receiver: "Bob".to_string(),
-            amount: 10.0,
+    fn higher_fee_replaces_pending_transaction() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        let send =
+            |fee| Transaction::new("Tilt".to_string(), "Bob".to_string(), 40 * COIN).with_fee(fee);
+        chain.submit_transaction(send(COIN / 10)).unwrap();
+        chain.submit_transaction(send(COIN / 5)).unwrap();
+        assert_eq!(chain.pending_transactions(), [send(COIN / 5)]);
+
+        assert!(matches!(
+            chain.submit_transaction(send(COIN / 10)),
+            Err(BlockchainError::ReplacementFeeTooLow { fee, replaced })
+                if fee == COIN / 10 && replaced == COIN / 5
+        ));
+        assert!(matches!(
+            chain.submit_transaction(send(20 * COIN)),
+            Err(BlockchainError::InsufficientFunds { .. })
+        ));
+        assert_eq!(chain.pending_transactions(), [send(COIN / 5)]);
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_balance("Bob"), 40 * COIN);
+    }
+
+    #[test]
+    fn expired_transaction_is_evicted_before_next_block() {
+        let start = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let clock = FakeClock {
+            next: std::sync::Mutex::new(start),
+            step: chrono::Duration::hours(1),
         };
+        let mut chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .clock(Box::new(clock))
+                .mempool_ttl(Duration::from_hours(2)),
+        )
+        .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        let unstamped = Transaction::new("Tilt".to_string(), "Carol".to_string(), COIN);
+        chain.submit_transaction(unstamped.clone()).unwrap();
+        assert_eq!(
+            chain.pending_transactions()[0].timestamp,
+            Some(start + chrono::Duration::hours(1))
+        );
+        assert_eq!(chain.evict_expired(start + chrono::Duration::hours(3)), 0);
 
-        let hash1 = Chain::hash(&transaction);
-        let hash2 = Chain::hash(&transaction);
+        // Two hours pass, so the next block is stamped three hours after the
+        // transaction.
+        chain.clock.now();
+        chain.clock.now();
+        chain.generate_new_block().unwrap();
+        assert_eq!(
+            chain.latest().unwrap().get_header().timestamp,
+            start + chrono::Duration::hours(4)
+        );
+        assert_eq!(chain.latest().unwrap().get_transactions()[1], unstamped);
+        assert_eq!(chain.get_balance("Bob"), 0);
+        assert!(chain.pending_transactions().is_empty());
+    }
 
-        assert_eq!(hash1, hash2);
+    #[test]
+    fn full_block_takes_highest_fees_over_earlier_transactions() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        assert!(chain.update_max_block_txs(2));
+        for (receiver, fee) in [
+            ("Bob", COIN / 100),
+            ("Carol", COIN / 5),
+            ("Dave", COIN / 10),
+        ] {
+            chain
+                .add_transaction("Tilt".to_string(), receiver.to_string(), COIN, Some(fee))
+                .unwrap();
+        }
+
+        chain.generate_new_block().unwrap();
+        let included = chain.latest().unwrap().get_transactions();
+        let receivers: Vec<&str> = included[1..].iter().map(|t| t.receiver.as_str()).collect();
+        assert_eq!(receivers, ["Carol", "Dave"]);
+        assert_eq!(included[0].amount, chain.block_subsidy(1) + 3 * COIN / 10);
+        assert_eq!(chain.pending_transactions()[0].receiver, "Bob");
     }
 
     #[test]
-    fn hex_to_string_converts_bytes_to_hex() {
-        let bytes = vec![0, 1, 10, 255];
-        let hex = Chain::hex_to_string(&bytes);
-        assert_eq!(hex, "00010aff");
+    fn chain_iterates_over_its_blocks_in_order() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain.mine_n(3).unwrap();
+
+        let mut count = 0;
+        for (index, block) in (0..).zip(&chain) {
+            assert_eq!(block.get_index(), index);
+            count += 1;
+        }
+        assert_eq!(count, chain.len());
+        assert_eq!(chain.iter().last(), chain.latest());
     }
 
     #[test]
-    fn last_hash_returns_hash_of_last_block() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        let last_hash = chain.last_hash();
+    fn block_stays_under_byte_limit_and_rejects_larger_blocks() {
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).max_block_bytes(4000)).unwrap();
+        for i in 0..30 {
+            chain
+                .submit_transaction(
+                    Transaction::new("Tilt".to_string(), format!("Bob{i}"), COIN)
+                        .with_memo("x".repeat(MAX_MEMO_LEN)),
+                )
+                .unwrap();
+        }
 
-        // Generate a new block and check that last_hash changes
-        chain.generate_new_block();
-        let new_last_hash = chain.last_hash();
+        chain.generate_new_block().unwrap();
+        let block = chain.latest().unwrap();
+        assert!(block.size() <= 4000, "{}", block.size());
+        let included = block.get_transactions().len() - 1;
+        assert!(included > 1);
+        assert_eq!(chain.pending_transactions().len(), 30 - included);
 
-        assert_ne!(last_hash, new_last_hash);
+        let mut block = chain.build_block().unwrap();
+        let extra = Transaction::new("Tilt".to_string(), "Carol".to_string(), COIN)
+            .with_memo("x".repeat(MAX_MEMO_LEN));
+        block.transactions.push(extra);
+        reseal(&mut block);
+        assert!(block.size() > 4000);
+        assert!(matches!(
+            chain.submit_block(block),
+            Err(BlockchainError::InvalidBlock(Tampering::TooLarge(2)))
+        ));
     }
 
     #[test]
-    fn update_difficulty_changes_chain_difficulty() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        chain.update_difficulty(2);
-        assert_eq!(chain.get_difficulty(), 2);
+    fn burned_coins_leave_circulation_but_not_total_supply() {
+        let burn = ZERO_HASH.to_string();
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).burn_address(burn.clone()))
+                .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), burn.clone(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let total = 100 * COIN;
+        assert_eq!(chain.total_supply(), total);
+        assert_eq!(chain.get_balance(&burn), 10 * COIN);
+        assert_eq!(chain.burned_supply(), 10 * COIN);
+        assert_eq!(chain.circulating_supply(), total - 10 * COIN);
+        assert!(!chain.stakes().contains_key(&burn));
+
+        assert!(matches!(
+            chain.add_transaction(burn.clone(), "Bob".to_string(), COIN, None),
+            Err(BlockchainError::BurnSender(sender)) if sender == burn
+        ));
+        chain
+            .current_transactions
+            .push(Transaction::new(burn, "Bob".to_string(), COIN));
+        let block = chain.build_block().unwrap();
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::InvalidSpend(2)))
+        ));
+        assert_eq!(chain.circulating_supply(), total - 10 * COIN);
     }
 
     #[test]
-    fn update_reward_changes_miner_reward() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        chain.update_reward(75.0);
-        assert!((chain.get_reward() - 75.0).abs() < f32::EPSILON);
+    fn replay_returns_ledger_and_rejects_overspend() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+        assert_eq!(
+            chain.replay().unwrap(),
+            BTreeMap::from([
+                ("Bob".to_string(), 10 * COIN),
+                ("Tilt".to_string(), 90 * COIN)
+            ])
+        );
+
+        chain.chains[1].transactions[1].amount = 500 * COIN;
+        reseal(&mut chain.chains[1]);
+        assert!(chain.is_valid());
+        assert!(matches!(
+            chain.replay(),
+            Err(BlockchainError::InvalidBlock(Tampering::Overdraft(1)))
+        ));
     }
 
     #[test]
-    fn get_merkle_handles_odd_number_of_transactions() {
-        let transactions = vec![
-            Transaction {
-                sender: "a".to_string(),
-                receiver: "b".to_string(),
-                amount: 1.0,
-            },
-            Transaction {
-                sender: "c".to_string(),
-                receiver: "d".to_string(),
-                amount: 2.0,
-            },
-            Transaction {
-                sender: "e".to_string(),
-                receiver: "f".to_string(),
-                amount: 3.0,
-            },
-        ];
+    fn reward_is_spendable_only_once_mature() {
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).coinbase_maturity(2)).unwrap();
+        let pay = |chain: &mut Chain| {
+            chain.add_transaction("Tilt".to_string(), "Bob".to_string(), 10 * COIN, None)
+        };
+        assert_eq!(chain.get_balance("Tilt"), 50 * COIN);
+        assert_eq!(chain.spendable_balance("Tilt"), 0);
+        assert!(matches!(
+            pay(&mut chain),
+            Err(BlockchainError::InsufficientFunds { available: 0, .. })
+        ));
 
-        let merkle = Chain::get_merkle(&transactions);
-        assert!(!merkle.is_empty());
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.spendable_balance("Tilt"), 0);
+        assert!(pay(&mut chain).is_err());
+
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_balance("Tilt"), 150 * COIN);
+        assert_eq!(chain.spendable_balance("Tilt"), 50 * COIN);
+        pay(&mut chain).unwrap();
+
+        let immature_reward = OutPoint {
+            txid: chain.chains[1].transactions[0].txid(HashAlgo::Sha256),
+            index: 0,
+        };
+        let spend = Transaction::spend(
+            "Tilt".to_string(),
+            vec![immature_reward.clone()],
+            vec![TxOut {
+                address: "Bob".to_string(),
+                amount: 50 * COIN,
+                script: None,
+            }],
+        );
+        assert!(matches!(
+            chain.submit_transaction(spend.clone()),
+            Err(BlockchainError::UnspendableOutput(outpoint)) if outpoint == immature_reward
+        ));
+
+        let mut block = chain.build_block().unwrap();
+        block.transactions.push(spend);
+        reseal(&mut block);
+        assert!(matches!(
+            chain.submit_block(block.clone()),
+            Err(BlockchainError::InvalidBlock(Tampering::InvalidSpend(3)))
+        ));
+
+        let mut candidate = chain.chains.clone();
+        candidate.push(block);
+        assert_eq!(
+            chain.replay_utxos(&candidate).err(),
+            Some(Tampering::InvalidSpend(3))
+        );
     }
 
     #[test]
-    fn get_latest_block_json_returns_none_for_empty_chain() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        chain.chains.clear(); // Artificially clear the chain
-        assert!(chain.get_latest_block_json().is_none());
+    fn reward_split_pays_each_address_its_share() {
+        let split = vec![("Dev".to_string(), 10)];
+        let mut chain =
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).reward_split(split)).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, Some(COIN))
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let coinbase = &chain.latest().unwrap().get_transactions()[0];
+        assert_eq!(coinbase.receiver, "Tilt");
+        assert_eq!(
+            coinbase.credits(),
+            [("Tilt", 459 * COIN / 10), ("Dev", 51 * COIN / 10)]
+        );
+        assert_eq!(chain.get_balance("Dev"), 10 * COIN + COIN / 10);
+        assert_eq!(
+            chain.get_balance("Tilt"),
+            45 * COIN + 459 * COIN / 10 - 2 * COIN
+        );
+        assert!(chain.is_valid());
+
+        let mut block = chain.build_block().unwrap();
+        block.transactions[0].outputs[1].amount += 1;
+        block.transactions[0].amount += 1;
+        reseal(&mut block);
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(
+                Tampering::WrongCoinbaseAmount(2)
+            ))
+        ));
+
+        let split = vec![("Dev".to_string(), 60), ("Ops".to_string(), 50)];
+        assert!(matches!(
+            Chain::from_config(ChainConfig::new("Tilt".to_string()).reward_split(split)),
+            Err(BlockchainError::InvalidRewardSplit(110))
+        ));
+
+        let mut saved = serde_json::to_value(chain.snapshot()).unwrap();
+        saved["reward_split"] = serde_json::json!([["Dev", 60], ["Ops", 50]]);
+        assert!(matches!(
+            Chain::from_json(saved.to_string().as_bytes()),
+            Err(BlockchainError::InvalidRewardSplit(110))
+        ));
     }
 
     #[test]
-    fn get_blocks_json_returns_expected_number_of_blocks() {
-        let mut chain = Chain::new("Tilt".to_string(), 1, None);
-        chain.generate_new_block();
-        chain.generate_new_block();
+    fn loading_resubmits_pending_transactions_and_checks_target() {
+        let mut chain = Chain::new("Tilt".to_string(), 1, None).unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        let mut saved = serde_json::to_value(chain.snapshot()).unwrap();
+        let pending = saved["current_transactions"][0].clone();
+        let overdraft = Transaction::new("Bob".to_string(), "Carol".to_string(), 1000 * COIN);
+        let mempool = saved["current_transactions"].as_array_mut().unwrap();
+        mempool.push(pending);
+        mempool.push(serde_json::to_value(overdraft).unwrap());
 
-        let blocks_json = chain.get_blocks_json();
-        assert_eq!(blocks_json.len(), 3); // Genesis + 2 new blocks
+        let loaded = Chain::from_json(saved.to_string().as_bytes()).unwrap();
+        assert_eq!(loaded.pending_transactions(), chain.pending_transactions());
+
+        saved["target_bits"] = 0x0480_0001.into();
+        assert!(matches!(
+            Chain::from_json(saved.to_string().as_bytes()),
+            Err(BlockchainError::InvalidTarget(0x0480_0001))
+        ));
+    }
+
+    #[test]
+    fn scripted_outputs_need_their_script_satisfied() {
+        let (mut chain, coin) = funded_utxo_chain();
+        let wallet = Wallet::new();
+        let lock = Transaction::spend(
+            "Tilt".to_string(),
+            vec![coin],
+            vec![
+                TxOut {
+                    address: wallet.address(),
+                    amount: 30 * COIN,
+                    script: Some(script::pay_to_public_key(&wallet.public_key())),
+                },
+                TxOut {
+                    address: "Tilt".to_string(),
+                    amount: 20 * COIN,
+                    script: Some(script::op_return(b"hello")),
+                },
+                TxOut {
+                    address: "Tilt".to_string(),
+                    amount: 0,
+                    script: Some(script::op_return(b"memo")),
+                },
+            ],
+        );
+        let txid = lock.txid(HashAlgo::Sha256);
+        chain.submit_transaction(lock).unwrap();
+        chain.generate_new_block().unwrap();
+        // Locked coins stay out of account balances.
+        assert_eq!(chain.get_balance("Tilt"), 50 * COIN);
+        assert_eq!(chain.get_balance(&wallet.address()), 0);
+        assert_eq!(chain.replay().unwrap().get("Tilt"), Some(&(50 * COIN)));
+        assert!(matches!(
+            chain.add_transaction(wallet.address(), "Bob".to_string(), 30 * COIN, None),
+            Err(BlockchainError::InsufficientFunds { available: 0, .. })
+        ));
+        assert_eq!(chain.find_transactions(&wallet.address()).len(), 1);
+        let output = |index| OutPoint {
+            txid: txid.clone(),
+            index,
+        };
+        let spend = |sender: String, input, amount| {
+            Transaction::spend(
+                sender,
+                vec![input],
+                vec![TxOut {
+                    address: "Bob".to_string(),
+                    amount,
+                    script: None,
+                }],
+            )
+        };
+
+        let burn = spend("Tilt".to_string(), output(1), 20 * COIN);
+        assert!(matches!(
+            chain.submit_transaction(burn.clone()),
+            Err(BlockchainError::InvalidScript)
+        ));
+        chain.current_transactions.push(burn);
+        let block = chain.build_block().unwrap();
+        assert!(matches!(
+            chain.verify_block(&block),
+            Err(BlockchainError::InvalidBlock(Tampering::InvalidSpend(2)))
+        ));
+        chain.current_transactions.clear();
+
+        let mut unlock = spend(wallet.address(), output(0), 30 * COIN);
+        assert!(matches!(
+            chain.submit_transaction(unlock.clone()),
+            Err(BlockchainError::InvalidSignature)
+        ));
+        unlock.sign(&wallet).unwrap();
+        chain.submit_transaction(unlock).unwrap();
+        chain.generate_new_block().unwrap();
+        assert_eq!(chain.get_balance("Bob"), 30 * COIN);
+        assert_eq!(chain.get_balance(&wallet.address()), 0);
+        assert!(chain.utxos().contains_key(&output(1)));
+        assert!(chain.is_valid());
+        assert_eq!(chain.replay().unwrap().get("Bob"), Some(&(30 * COIN)));
     }
 }
@@ -0,0 +1,266 @@
+//! Non-interactive command-line interface.
+//!
+//! Each subcommand loads the chain from `--file` (creating a new one if the file
+//! does not exist), performs a single operation, and saves any changes back.
+//! With `--json`, results are printed as JSON with amounts in base units.
+use blockchain_sandbox::amount::{format_amount, parse_amount};
+use blockchain_sandbox::blockchain::{Chain, Transaction};
+use blockchain_sandbox::config::ChainConfig;
+use blockchain_sandbox::error::BlockchainError;
+use blockchain_sandbox::hash::HashAlgo;
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Command-line arguments of the blockchain sandbox.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    /// Chain file to read and update
+    #[arg(long, global = true, default_value = "chain.json")]
+    pub file: PathBuf,
+    /// Miner address used when a new chain has to be created
+    #[arg(long, global = true, default_value = "miner")]
+    pub miner: String,
+    /// Mining difficulty used when a new chain has to be created
+    #[arg(long, global = true, default_value_t = 2)]
+    pub difficulty: u32,
+    /// Hide the mining progress bar
+    #[arg(long, global = true)]
+    pub quiet: bool,
+    /// Print results as JSON instead of text; implies `--quiet`
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Hash algorithm (sha256, sha256d, sha3-256, blake3) used when a new chain has to be created
+    #[arg(long, global = true, default_value_t = HashAlgo::Sha256)]
+    pub hash_algo: HashAlgo,
+    /// Start the interactive menu on the chain saved in this file instead of a new one
+    #[arg(long, value_name = "PATH")]
+    pub load: Option<PathBuf>,
+    /// Serve the REST API on this port; takes precedence over any subcommand
+    #[cfg(feature = "server")]
+    #[arg(long, value_name = "PORT")]
+    pub serve: Option<u16>,
+    /// Operation to perform; the interactive menu starts when omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Operations available without the interactive menu.
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// Mine new blocks, the first one with the pending transactions
+    Mine {
+        /// Number of blocks to mine
+        #[arg(default_value_t = 1)]
+        count: u64,
+    },
+    /// Add a transaction to the pending transactions pool
+    Tx {
+        /// Address of the sender
+        #[arg(long)]
+        from: String,
+        /// Address of the receiver
+        #[arg(long)]
+        to: String,
+        /// Amount to transfer, in coins
+        #[arg(long, value_parser = parse_coins)]
+        amount: u64,
+        /// Fee paid to the miner, in coins
+        #[arg(long, value_parser = parse_coins)]
+        fee: Option<u64>,
+        /// Annotation attached to the transaction
+        #[arg(long)]
+        memo: Option<String>,
+    },
+    /// Add the transactions of a JSON file to the pending transactions pool
+    Import {
+        /// JSON file holding an array of `{sender, receiver, amount}` objects
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Print every block of the chain
+    Show,
+    /// Print the confirmed balance of an address
+    Balance {
+        /// Address to compute the balance for
+        #[arg(long)]
+        address: String,
+        /// Count only blocks up to and including this height
+        #[arg(long)]
+        height: Option<u64>,
+    },
+    /// Verify the integrity of the chain
+    Validate,
+}
+
+/// Parses a `--amount` or `--fee` value given in coins into base units.
+fn parse_coins(coins: &str) -> Result<u64, String> {
+    parse_amount(coins).ok_or_else(|| {
+        format!("{coins:?} is not an amount of coins with at most eight decimal places")
+    })
+}
+
+/// Loads the chain from `--file`, or creates a new one if the file does not exist.
+///
+/// # Arguments
+///
+/// * `cli` - Parsed command-line arguments
+///
+/// # Returns
+///
+/// The loaded or newly created chain, or the error that prevented it
+pub(crate) fn open(cli: &Cli) -> Result<Chain, BlockchainError> {
+    if cli.file.exists() {
+        Chain::load_from_file(&cli.file)
+    } else {
+        Chain::from_config(
+            ChainConfig::new(cli.miner.clone())
+                .difficulty(cli.difficulty)
+                .hash_algo(cli.hash_algo)
+                .quiet(cli.quiet || cli.json),
+        )
+    }
+}
+
+/// Prints a value as pretty-printed JSON on stdout.
+fn print_json(value: &impl serde::Serialize) -> Result<(), BlockchainError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Prints the blocks mined from height `first` on, with the hashes tried and
+/// hashrate of each.
+fn print_mined(chain: &Chain, first: usize) {
+    let stats = chain.mining_stats();
+    for block in &chain.get_chain()[first..] {
+        print!(
+            "Mined block #{} with {} transactions",
+            block.get_index(),
+            block.get_transactions().len()
+        );
+        match stats
+            .iter()
+            .find(|&&(index, _, _)| index == block.get_index())
+        {
+            Some(&(_, duration, attempts)) => println!(
+                " ({attempts} attempts, {:.0} H/s)",
+                attempts as f64 / duration.as_secs_f64().max(f64::EPSILON)
+            ),
+            None => println!(),
+        }
+    }
+}
+
+/// Runs a single subcommand against the chain file.
+///
+/// A chain that is modified or newly created is saved back to the file.
+///
+/// # Arguments
+///
+/// * `cli` - Parsed command-line arguments
+/// * `command` - Subcommand to run
+///
+/// # Returns
+///
+/// `Ok(())` on success, or the error that stopped the command. `validate`
+/// reports a corrupt chain as [`BlockchainError::InvalidChain`].
+pub(crate) fn run(cli: &Cli, command: &Command) -> Result<(), BlockchainError> {
+    let existed = cli.file.exists();
+    let mut chain = open(cli)?;
+    chain.set_quiet(cli.quiet || cli.json);
+
+    let changed = match command {
+        Command::Mine { count } => {
+            let first = chain.len();
+            chain.mine_n(*count)?;
+            let mined = &chain.get_chain()[first..];
+            if cli.json {
+                print_json(&mined)?;
+            } else {
+                print_mined(&chain, first);
+            }
+            *count > 0
+        }
+        Command::Tx {
+            from,
+            to,
+            amount,
+            fee,
+            memo,
+        } => {
+            let mut transaction = Transaction::new(from.clone(), to.clone(), *amount)
+                .with_fee(fee.unwrap_or(0))
+                .with_timestamp(chrono::Utc::now());
+            if let Some(memo) = memo {
+                transaction = transaction.with_memo(memo.clone());
+            }
+            chain.submit_transaction(transaction.clone())?;
+            if cli.json {
+                print_json(&transaction)?;
+            } else {
+                println!(
+                    "Transaction added: {from} -> {to} ({})",
+                    format_amount(*amount)
+                );
+            }
+            true
+        }
+        Command::Import { input } => {
+            let accepted = chain.import_transactions(input)?;
+            if cli.json {
+                print_json(&json!({ "accepted": accepted }))?;
+            } else {
+                println!("Imported {accepted} transactions from {}", input.display());
+            }
+            accepted > 0
+        }
+        Command::Show => {
+            if cli.json {
+                print_json(chain.get_chain())?;
+            } else {
+                for block in &chain {
+                    println!("{block}");
+                    for transaction in block.get_transactions() {
+                        println!("  {transaction}");
+                    }
+                }
+            }
+            false
+        }
+        Command::Balance { address, height } => {
+            let balance = height.map_or_else(
+                || chain.get_balance(address),
+                |height| chain.balance_at(address, height),
+            );
+            if cli.json {
+                print_json(&json!({ "address": address, "balance": balance }))?;
+            } else {
+                println!("{}", format_amount(balance));
+            }
+            false
+        }
+        Command::Validate => {
+            let tampering = chain.find_tampering();
+            if cli.json {
+                print_json(&json!({
+                    "valid": tampering.is_none(),
+                    "tampering": tampering.as_ref().map(ToString::to_string),
+                }))?;
+            }
+            if let Some(tampering) = tampering {
+                eprintln!("Tampering detected at {tampering}");
+                return Err(BlockchainError::InvalidChain);
+            }
+            if !cli.json {
+                println!("Chain is valid.");
+            }
+            false
+        }
+    };
+
+    if changed || !existed {
+        chain.save_to_file(&cli.file)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Time source used to stamp newly mined blocks.
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// A source of the current time.
+///
+/// Chains read block timestamps from their clock, so tests can supply a fake
+/// clock to make timestamps deterministic.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real UTC wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
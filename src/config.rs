@@ -0,0 +1,480 @@
+//! Settings for creating a chain with [`Chain::from_config`](crate::Chain::from_config).
+use crate::amount::COIN;
+use crate::blockchain::{
+    DEFAULT_HALVING_INTERVAL, DEFAULT_MAX_BLOCK_BYTES, DEFAULT_MAX_BLOCK_TXS, DEFAULT_MAX_MEMPOOL,
+    DEFAULT_MAX_MINING_ITERATIONS, DEFAULT_MEMPOOL_TTL, DEFAULT_TARGET_BLOCK_TIME,
+};
+use crate::clock::{Clock, SystemClock};
+use crate::consensus::{Consensus, ProofOfWork};
+use crate::hash::HashAlgo;
+use crate::storage::{MemoryStorage, Storage};
+use crate::target::DifficultyUnit;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Default base mining reward, 50 coins.
+pub const DEFAULT_REWARD: u64 = 50 * COIN;
+
+/// Settings of a new chain, built with chained setters.
+///
+/// Every setting except the miner address has a default, so only the ones that
+/// differ need to be set:
+///
+/// ```
+/// use blockchain_sandbox::amount::COIN;
+/// use blockchain_sandbox::{Chain, ChainConfig};
+///
+/// let config = ChainConfig::new("miner".to_string())
+///     .difficulty(1)
+///     .reward(25 * COIN);
+/// let chain = Chain::from_config(config)?;
+/// assert_eq!(chain.get_reward(), 25 * COIN);
+/// # Ok::<(), blockchain_sandbox::error::BlockchainError>(())
+/// ```
+///
+/// Values that the `update_*` methods of [`Chain`](crate::Chain) would reject are replaced
+/// when the chain is created: the difficulty is clamped to the
+/// [`max_difficulty`](DifficultyUnit::max_difficulty) of its unit, zero limits and thread
+/// counts become 1, and a zero target block time, mempool TTL, or block size limit falls back
+/// to the default.
+#[derive(Debug)]
+pub struct ChainConfig {
+    /// Address where mining rewards are sent
+    pub(crate) miner_address: String,
+    /// Initial mining difficulty
+    pub(crate) difficulty: u32,
+    /// Base mining reward before halvings, in base units
+    pub(crate) reward: u64,
+    /// Number of blocks between reward halvings
+    pub(crate) halving_interval: u64,
+    /// Number of blocks mined on top of a coinbase before it can be spent
+    pub(crate) coinbase_maturity: u64,
+    /// Addresses paid a percentage of each coinbase besides the miner
+    pub(crate) reward_split: Vec<(String, u32)>,
+    /// Number of blocks between difficulty retargets, or 0 to disable them
+    pub(crate) retarget_interval: u64,
+    /// Block time targeted by difficulty retargeting
+    pub(crate) target_block_time: Duration,
+    /// Maximum number of pending transactions
+    pub(crate) max_mempool: usize,
+    /// Age after which a pending transaction is evicted
+    pub(crate) mempool_ttl: Duration,
+    /// Maximum number of pending transactions included in a block
+    pub(crate) max_block_txs: usize,
+    /// Maximum length of a block's binary encoding, in bytes
+    pub(crate) max_block_bytes: usize,
+    /// Maximum number of hashes tried while mining a single block
+    pub(crate) max_mining_iterations: u64,
+    /// Number of threads mining a block, or None for one per available CPU
+    pub(crate) mining_threads: Option<usize>,
+    /// Hash function for headers, transactions, and Merkle trees
+    pub(crate) hash_algo: HashAlgo,
+    /// Compact target of every block, or None to use the difficulty
+    pub(crate) target_bits: Option<u32>,
+    /// Whether the difficulty counts leading zero hex digits or bits
+    pub(crate) difficulty_unit: DifficultyUnit,
+    /// Whether the miner and transaction addresses must be checksummed
+    pub(crate) check_addresses: bool,
+    /// Address whose coins are burned, if any
+    pub(crate) burn_address: Option<String>,
+    /// Whether mining progress and mined blocks are not printed
+    pub(crate) quiet: bool,
+    /// Whether operations are recorded in the audit log
+    pub(crate) audit: bool,
+    /// Rule choosing the producer of each block
+    pub(crate) consensus: Box<dyn Consensus>,
+    /// Backend keeping the blocks
+    pub(crate) storage: Box<dyn Storage>,
+    /// Time source for block timestamps
+    pub(crate) clock: Box<dyn Clock>,
+    /// Timestamp of the genesis block, or None to read it from the clock
+    pub(crate) genesis_timestamp: Option<DateTime<Utc>>,
+    /// Memo of the genesis coinbase, or empty for the default
+    pub(crate) genesis_message: String,
+}
+
+impl ChainConfig {
+    /// Creates the default settings for a chain paying rewards to `miner_address`.
+    ///
+    /// The defaults match [`Chain::new`](crate::Chain::new) with difficulty 1 and no custom reward.
+    pub fn new(miner_address: String) -> Self {
+        Self {
+            miner_address,
+            difficulty: 1,
+            reward: DEFAULT_REWARD,
+            halving_interval: DEFAULT_HALVING_INTERVAL,
+            coinbase_maturity: 0,
+            reward_split: Vec::new(),
+            retarget_interval: 0,
+            target_block_time: DEFAULT_TARGET_BLOCK_TIME,
+            max_mempool: DEFAULT_MAX_MEMPOOL,
+            mempool_ttl: DEFAULT_MEMPOOL_TTL,
+            max_block_txs: DEFAULT_MAX_BLOCK_TXS,
+            max_block_bytes: DEFAULT_MAX_BLOCK_BYTES,
+            max_mining_iterations: DEFAULT_MAX_MINING_ITERATIONS,
+            mining_threads: None,
+            hash_algo: HashAlgo::default(),
+            target_bits: None,
+            difficulty_unit: DifficultyUnit::HexDigits,
+            check_addresses: false,
+            burn_address: None,
+            quiet: false,
+            audit: false,
+            consensus: Box::new(ProofOfWork),
+            storage: Box::new(MemoryStorage),
+            clock: Box::new(SystemClock),
+            genesis_timestamp: None,
+            genesis_message: String::new(),
+        }
+    }
+
+    /// Sets the initial mining difficulty.
+    #[must_use]
+    pub const fn difficulty(mut self, difficulty: u32) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Sets the base mining reward, in base units.
+    #[must_use]
+    pub const fn reward(mut self, reward: u64) -> Self {
+        self.reward = reward;
+        self
+    }
+
+    /// Splits the coinbase of every block mined: each `(address, share)`
+    /// receives `share` percent of its subsidy and fees, rounded down, and the
+    /// miner receives the rest as the first output.
+    ///
+    /// [`Chain::from_config`](crate::Chain::from_config) fails with
+    /// [`BlockchainError::InvalidRewardSplit`](crate::BlockchainError::InvalidRewardSplit)
+    /// if the shares add up to more than 100.
+    #[must_use]
+    pub fn reward_split(mut self, split: Vec<(String, u32)>) -> Self {
+        self.reward_split = split;
+        self
+    }
+
+    /// Sets the number of blocks between reward halvings.
+    #[must_use]
+    pub const fn halving_interval(mut self, interval: u64) -> Self {
+        self.halving_interval = interval;
+        self
+    }
+
+    /// Makes the reward of each block spendable only once `blocks` more
+    /// blocks have been mined on top of it.
+    ///
+    /// 0 by default, so rewards can be spent right away.
+    #[must_use]
+    pub const fn coinbase_maturity(mut self, blocks: u64) -> Self {
+        self.coinbase_maturity = blocks;
+        self
+    }
+
+    /// Enables difficulty retargeting every `interval` blocks towards
+    /// `target_block_time`.
+    #[must_use]
+    pub const fn retarget(mut self, interval: u64, target_block_time: Duration) -> Self {
+        self.retarget_interval = interval;
+        self.target_block_time = target_block_time;
+        self
+    }
+
+    /// Sets the maximum number of pending transactions.
+    #[must_use]
+    pub const fn max_mempool(mut self, max_mempool: usize) -> Self {
+        self.max_mempool = max_mempool;
+        self
+    }
+
+    /// Sets the age after which a pending transaction is evicted, see
+    /// [`Chain::evict_expired`](crate::Chain::evict_expired).
+    #[must_use]
+    pub const fn mempool_ttl(mut self, ttl: Duration) -> Self {
+        self.mempool_ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum number of pending transactions included in a block.
+    #[must_use]
+    pub const fn max_block_txs(mut self, max_block_txs: usize) -> Self {
+        self.max_block_txs = max_block_txs;
+        self
+    }
+
+    /// Sets the maximum length of a block's binary encoding, in bytes.
+    ///
+    /// Mined blocks leave pending the transactions that would not fit, and
+    /// submitted blocks over the limit are rejected; see
+    /// [`Block::size`](crate::Block::size).
+    #[must_use]
+    pub const fn max_block_bytes(mut self, max_block_bytes: usize) -> Self {
+        self.max_block_bytes = max_block_bytes;
+        self
+    }
+
+    /// Sets the maximum number of hashes tried while mining a single block.
+    #[must_use]
+    pub const fn max_mining_iterations(mut self, max_iterations: u64) -> Self {
+        self.max_mining_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the number of threads mining a block.
+    #[must_use]
+    pub const fn mining_threads(mut self, threads: usize) -> Self {
+        self.mining_threads = Some(threads);
+        self
+    }
+
+    /// Sets the hash function for headers, transactions, and Merkle trees.
+    #[must_use]
+    pub const fn hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self
+    }
+
+    /// Sets whether the difficulty counts leading zero hex digits, the default,
+    /// or leading zero bits of the raw hash.
+    #[must_use]
+    pub const fn difficulty_unit(mut self, unit: DifficultyUnit) -> Self {
+        self.difficulty_unit = unit;
+        self
+    }
+
+    /// Makes every block meet a 256-bit target in Bitcoin's compact `nBits`
+    /// format instead of the leading-zero difficulty.
+    ///
+    /// Retargeting only adjusts the difficulty, so the target stays fixed.
+    /// [`Chain::from_config`](crate::Chain::from_config) fails with
+    /// [`BlockchainError::InvalidTarget`](crate::BlockchainError::InvalidTarget)
+    /// if `bits` is not a valid encoding.
+    #[must_use]
+    pub const fn target_bits(mut self, bits: u32) -> Self {
+        self.target_bits = Some(bits);
+        self
+    }
+
+    /// Requires the miner address and the sender and receiver of every
+    /// transaction to be checksummed [`address`](crate::address)es, so mistyped
+    /// ones are rejected instead of receiving coins nobody can spend.
+    ///
+    /// Off by default, so free-form names like `"Bob"` work.
+    /// [`Chain::from_config`](crate::Chain::from_config) fails with
+    /// [`BlockchainError::InvalidAddress`](crate::BlockchainError::InvalidAddress)
+    /// if the miner address does not check out.
+    #[must_use]
+    pub const fn check_addresses(mut self, check: bool) -> Self {
+        self.check_addresses = check;
+        self
+    }
+
+    /// Makes `address` a burn address: coins sent to it accumulate in its
+    /// balance but can never be spent, as it is rejected as a sender.
+    ///
+    /// Burned coins still count towards
+    /// [`Chain::total_supply`](crate::Chain::total_supply), but not towards
+    /// [`Chain::circulating_supply`](crate::Chain::circulating_supply). The
+    /// burn address need not be checksummed when
+    /// [`check_addresses`](Self::check_addresses) is on, so an all-zero one
+    /// works.
+    #[must_use]
+    pub fn burn_address(mut self, address: String) -> Self {
+        self.burn_address = Some(address);
+        self
+    }
+
+    /// Hides the mining progress bar and mined blocks, including the genesis
+    /// block mined by [`Chain::from_config`](crate::Chain::from_config), as
+    /// [`Chain::set_quiet`](crate::Chain::set_quiet).
+    #[must_use]
+    pub const fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Records the chain's operations in an [`audit`](crate::audit) log,
+    /// starting with the genesis block, as
+    /// [`Chain::set_audit`](crate::Chain::set_audit).
+    #[must_use]
+    pub const fn audit(mut self, audit: bool) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Sets the rule choosing the producer of each block, by default
+    /// [`ProofOfWork`].
+    #[must_use]
+    pub fn consensus(mut self, consensus: Box<dyn Consensus>) -> Self {
+        self.consensus = consensus;
+        self
+    }
+
+    /// Sets the backend keeping the blocks, by default [`MemoryStorage`].
+    ///
+    /// If it already holds blocks, [`Chain::from_config`](crate::Chain::from_config)
+    /// resumes from them instead of mining a genesis block.
+    #[must_use]
+    pub fn storage(mut self, storage: Box<dyn Storage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Sets the time source for block timestamps.
+    #[must_use]
+    pub fn clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Fixes the timestamp of the genesis block instead of reading the clock.
+    #[must_use]
+    pub const fn genesis_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.genesis_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Embeds a message in the genesis block as the memo of its coinbase.
+    ///
+    /// The message is part of the genesis Merkle root, and so of its hash,
+    /// which tells apart chains created with different messages. It may be at
+    /// most [`MAX_MEMO_LEN`](crate::blockchain::MAX_MEMO_LEN) bytes long.
+    #[must_use]
+    pub fn genesis_message(mut self, message: String) -> Self {
+        self.genesis_message = message;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{Chain, MAX_MEMO_LEN};
+
+    #[test]
+    fn from_config_applies_custom_reward_and_difficulty() {
+        let chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .difficulty(2)
+                .reward(25 * COIN),
+        )
+        .unwrap();
+
+        assert_eq!(chain.get_difficulty(), 2);
+        assert_eq!(chain.get_reward(), 25 * COIN);
+        assert_eq!(chain.get_balance("Tilt"), 25 * COIN);
+        assert!(chain.genesis().unwrap().get_hash().starts_with("00"));
+    }
+
+    #[test]
+    fn from_config_applies_limits_and_hash_algo() {
+        let chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .halving_interval(10)
+                .max_mempool(5)
+                .max_block_txs(2)
+                .mining_threads(3)
+                .hash_algo(HashAlgo::Blake3),
+        )
+        .unwrap();
+
+        assert_eq!(chain.get_halving_interval(), 10);
+        assert_eq!(chain.get_max_mempool(), 5);
+        assert_eq!(chain.get_max_block_txs(), 2);
+        assert_eq!(chain.get_mining_threads(), 3);
+        assert_eq!(chain.get_hash_algo(), HashAlgo::Blake3);
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn from_config_replaces_zero_limits() {
+        let chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .max_mempool(0)
+                .mining_threads(0),
+        )
+        .unwrap();
+
+        assert_eq!(chain.get_max_mempool(), 1);
+        assert_eq!(chain.get_mining_threads(), 1);
+    }
+
+    #[test]
+    fn from_config_mines_blocks_below_compact_target() {
+        let mut chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .difficulty(0)
+                .target_bits(0x2100_7fff),
+        )
+        .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_target_bits(), Some(0x2100_7fff));
+        for block in &chain {
+            assert_eq!(block.get_header().get_bits(), Some(0x2100_7fff));
+            assert!(block.get_hash() < "8");
+        }
+        assert!(chain.is_valid());
+    }
+
+    #[test]
+    fn from_config_rejects_invalid_compact_target() {
+        let err = Chain::from_config(ChainConfig::new("Tilt".to_string()).target_bits(0x0480_0001))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::BlockchainError::InvalidTarget(0x0480_0001)
+        ));
+    }
+
+    #[test]
+    fn from_config_counts_difficulty_in_bits() {
+        let mut chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .difficulty(6)
+                .difficulty_unit(DifficultyUnit::Bits),
+        )
+        .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_difficulty_unit(), DifficultyUnit::Bits);
+        for block in &chain {
+            // Six zero bits are a whole zero hex digit followed by one below 4.
+            assert!(block.get_hash().starts_with('0'));
+            assert!(block.get_hash()[1..2] < *"4");
+        }
+        assert!(chain.is_valid());
+        assert!(chain.update_difficulty(200).is_ok());
+    }
+
+    #[test]
+    fn genesis_message_is_bound_into_genesis_hash() {
+        let timestamp = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let genesis = |message: &str| {
+            let chain = Chain::from_config(
+                ChainConfig::new("Tilt".to_string())
+                    .genesis_timestamp(timestamp)
+                    .genesis_message(message.to_string()),
+            )
+            .unwrap();
+            chain.genesis().unwrap().clone()
+        };
+        let times = genesis("The Times 03/Jan/2009 Chancellor on brink of second bailout");
+        let sandbox = genesis("Hello from the sandbox");
+
+        assert_eq!(
+            times.get_transactions()[0].memo.as_deref(),
+            Some("The Times 03/Jan/2009 Chancellor on brink of second bailout")
+        );
+        assert_ne!(times.get_hash(), sandbox.get_hash());
+        assert!(matches!(
+            Chain::from_config(
+                ChainConfig::new("Tilt".to_string()).genesis_message("x".repeat(MAX_MEMO_LEN + 1))
+            ),
+            Err(crate::BlockchainError::MemoTooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+//! Rules deciding who may produce each block, for comparing consensus models.
+//!
+//! Under [`ProofOfWork`], the default, any miner may produce the next block by
+//! solving its proof of work. Under [`ProofOfStake`], the producer is drawn
+//! from the funded addresses, weighted by their balances.
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A rule choosing the producer of each block.
+///
+/// Chains built with [`ChainConfig::consensus`](crate::ChainConfig::consensus)
+/// pay the reward of the blocks they mine to the chosen producer and reject
+/// submitted blocks paying anyone else.
+pub trait Consensus: fmt::Debug + Send + Sync {
+    /// Chooses the address entitled to produce a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `stakes` - Confirmed balance of every funded address before the block,
+    ///   in base units
+    /// * `height` - Index of the block
+    ///
+    /// # Returns
+    ///
+    /// The producer, or None if any miner may produce the block
+    fn producer(&self, stakes: &BTreeMap<String, u64>, height: u64) -> Option<String>;
+}
+
+/// Proof of work: whoever finds a valid nonce produces the block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProofOfWork;
+
+impl Consensus for ProofOfWork {
+    fn producer(&self, _stakes: &BTreeMap<String, u64>, _height: u64) -> Option<String> {
+        None
+    }
+}
+
+/// Proof of stake: the producer of each block is drawn from the funded
+/// addresses, each with a chance proportional to its balance.
+///
+/// The draw depends only on the stakes, the height, and the seed, so every
+/// node with the same chain and seed agrees on the producer. Blocks are still
+/// hashed at the chain's difficulty; with difficulty 0 stake alone decides.
+/// Until any coins exist, the chain's miner produces the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOfStake {
+    /// Value mixed into every draw
+    seed: u64,
+}
+
+impl ProofOfStake {
+    /// Creates a proof-of-stake rule drawing producers with the given seed.
+    pub const fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl Consensus for ProofOfStake {
+    fn producer(&self, stakes: &BTreeMap<String, u64>, height: u64) -> Option<String> {
+        let total: u128 = stakes.values().map(|&stake| u128::from(stake)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.to_le_bytes());
+        hasher.update(height.to_le_bytes());
+        let mut draw = [0; 16];
+        draw.copy_from_slice(&hasher.finalize()[..16]);
+        let mut draw = u128::from_be_bytes(draw) % total;
+        for (address, &stake) in stakes {
+            let stake = u128::from(stake);
+            if draw < stake {
+                return Some(address.clone());
+            }
+            draw -= stake;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::COIN;
+    use crate::blockchain::{Chain, Tampering};
+    use crate::config::ChainConfig;
+    use crate::error::BlockchainError;
+
+    fn stakes() -> BTreeMap<String, u64> {
+        BTreeMap::from([
+            ("Alice".to_string(), COIN),
+            ("Bob".to_string(), 3 * COIN),
+            ("Carol".to_string(), 0),
+        ])
+    }
+
+    #[test]
+    fn stake_draw_is_deterministic_and_weighted_by_stake() {
+        let stakes = stakes();
+        let draws = |seed| {
+            (0..4000)
+                .map(|height| ProofOfStake::new(seed).producer(&stakes, height).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let producers = draws(42);
+        assert_eq!(producers, draws(42));
+        assert_ne!(producers, draws(43));
+
+        let count = |name| producers.iter().filter(|p| *p == name).count();
+        assert_eq!(count("Carol"), 0);
+        assert!(count("Bob") > 2 * count("Alice"), "{}", count("Bob"));
+        assert!(count("Alice") > 0);
+    }
+
+    #[test]
+    fn no_stake_means_no_producer() {
+        assert_eq!(ProofOfStake::new(1).producer(&BTreeMap::new(), 0), None);
+        assert_eq!(ProofOfWork.producer(&stakes(), 0), None);
+    }
+
+    #[test]
+    fn stake_chain_pays_chosen_producer_and_rejects_others() {
+        let mut chain = Chain::from_config(
+            ChainConfig::new("Tilt".to_string())
+                .difficulty(0)
+                .consensus(Box::new(ProofOfStake::new(7))),
+        )
+        .unwrap();
+        chain
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), 20 * COIN, None)
+            .unwrap();
+        for _ in 0..5 {
+            let producer = chain.producer().unwrap();
+            chain.generate_new_block().unwrap();
+            let coinbase = &chain.latest().unwrap().get_transactions()[0];
+            assert_eq!(coinbase.receiver, producer);
+        }
+
+        assert!(matches!(
+            chain.generate_new_block_to("Mallory"),
+            Err(BlockchainError::InvalidBlock(Tampering::WrongProducer(6)))
+        ));
+        assert!(chain.is_valid());
+    }
+}
@@ -0,0 +1,138 @@
+//! Canonical byte encoding of the items a chain hashes.
+//!
+//! Numbers are written big-endian at a fixed width, strings and sequences are
+//! prefixed with their length as a `u64`, and optional values with a `0` or `1`
+//! tag byte. The encoding does not depend on serde, so hashes stay the same
+//! across serde versions and platforms.
+use chrono::{DateTime, Utc};
+
+/// An item with a canonical byte encoding.
+pub trait Encode {
+    /// Appends the encoding of the item to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Returns the encoding of the item.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+}
+
+impl Encode for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Encode for u32 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl Encode for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl Encode for i64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl Encode for f32 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.to_bits().encode(out);
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out);
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(item) => {
+                out.push(1);
+                item.encode(out);
+            }
+        }
+    }
+}
+
+impl Encode for DateTime<Utc> {
+    /// Encodes seconds since the Unix epoch followed by the subsecond nanoseconds.
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.timestamp().encode(out);
+        self.timestamp_subsec_nanos().encode(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_big_endian_at_fixed_width() {
+        assert_eq!(1u32.to_bytes(), [0, 0, 0, 1]);
+        assert_eq!(1u64.to_bytes(), [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(1.0f32.to_bytes(), 1.0f32.to_bits().to_be_bytes());
+    }
+
+    #[test]
+    fn strings_are_length_prefixed() {
+        assert_eq!("ab".to_bytes(), [0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b']);
+        // Without the prefix both pairs would encode to "abc".
+        assert_ne!(
+            ["ab", "c"].as_slice().to_bytes(),
+            ["a", "bc"].as_slice().to_bytes()
+        );
+    }
+
+    #[test]
+    fn options_are_tagged() {
+        assert_eq!(None::<u8>.to_bytes(), [0]);
+        assert_eq!(Some(7u8).to_bytes(), [1, 7]);
+    }
+}
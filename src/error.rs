@@ -0,0 +1,144 @@
+//! Error type returned by fallible blockchain operations.
+use crate::amount::format_amount;
+use crate::blockchain::{OutPoint, Tampering};
+use thiserror::Error;
+
+/// Errors that can occur while building, mining, or persisting a chain.
+#[derive(Debug, Error)]
+pub enum BlockchainError {
+    /// An item could not be serialized to or deserialized from JSON
+    #[error("serialization failed: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// Reading or writing a chain file failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Encoding the chain in the binary format failed
+    #[error("binary encoding failed: {0}")]
+    BinaryEncode(#[from] bincode::error::EncodeError),
+    /// A binary chain file is malformed
+    #[error("binary decoding failed: {0}")]
+    BinaryDecode(#[from] bincode::error::DecodeError),
+    /// Reading or writing a [`SledStorage`](crate::storage::SledStorage)
+    /// database failed
+    #[cfg(feature = "sled")]
+    #[error("storage error: {0}")]
+    Sled(#[from] sled::Error),
+    /// The requested mining difficulty is not supported
+    #[error("difficulty {difficulty} exceeds the maximum of {max}")]
+    InvalidDifficulty {
+        /// Requested difficulty
+        difficulty: u32,
+        /// Highest supported difficulty
+        max: u32,
+    },
+    /// The compact proof-of-work target is not a valid encoding
+    #[error("invalid compact target {0:#010x}")]
+    InvalidTarget(u32),
+    /// The address is not a well-formed checksummed address
+    #[error("invalid address {0:?}: malformed or bad checksum")]
+    InvalidAddress(String),
+    /// The shares of a reward split add up to more than the whole reward
+    #[error("reward shares add up to {0}%, more than 100%")]
+    InvalidRewardSplit(u64),
+    /// The transaction amount is zero
+    #[error("amount {} must be a positive number", format_amount(*.0))]
+    InvalidAmount(u64),
+    /// The transaction memo is longer than allowed
+    #[error("memo is {len} bytes, more than the maximum of {max}")]
+    MemoTooLong {
+        /// Length of the memo in bytes
+        len: usize,
+        /// Highest allowed length in bytes
+        max: usize,
+    },
+    /// The transaction does not satisfy the script of an output it spends
+    #[error("output script is not satisfied")]
+    InvalidScript,
+    /// The transaction is unsigned or its signature does not verify
+    #[error("missing or invalid signature")]
+    InvalidSignature,
+    /// The sender cannot cover the amount plus fee
+    #[error(
+        "insufficient funds: {address} has {} available but needs {}",
+        format_amount(*available),
+        format_amount(*required)
+    )]
+    InsufficientFunds {
+        /// Address of the sender
+        address: String,
+        /// Confirmed balance minus amounts already pending from the sender, in
+        /// base units
+        available: u64,
+        /// Amount plus fee of the rejected transaction, in base units
+        required: u64,
+    },
+    /// The transaction spends an output that does not exist, is already spent
+    /// or claimed by a pending transaction, or belongs to another address
+    #[error("output {0} cannot be spent")]
+    UnspendableOutput(OutPoint),
+    /// The inputs of the transaction do not add up to its outputs plus fee
+    #[error(
+        "inputs add up to {} but outputs plus fee need {}",
+        format_amount(*inputs),
+        format_amount(*required)
+    )]
+    UnbalancedTransaction {
+        /// Total of the spent outputs, in base units
+        inputs: u64,
+        /// Total of the created outputs plus the fee, in base units
+        required: u64,
+    },
+    /// The sender is the chain's burn address, whose coins cannot be spent
+    #[error("address {0:?} burns coins and cannot send them")]
+    BurnSender(String),
+    /// A transaction with the same id is already pending
+    #[error("transaction {0} is already pending")]
+    DuplicateTransaction(String),
+    /// A pending transaction from the same sender does the same thing for at
+    /// least the same fee
+    #[error(
+        "replacement fee {} must be higher than the pending transaction's {}",
+        format_amount(*fee),
+        format_amount(*replaced)
+    )]
+    ReplacementFeeTooLow {
+        /// Fee of the rejected transaction, in base units
+        fee: u64,
+        /// Fee of the pending transaction it would replace, in base units
+        replaced: u64,
+    },
+    /// The pending pool is full and the transaction does not outbid any entry
+    #[error("mempool is full ({0} transactions)")]
+    MempoolFull(usize),
+    /// Mining tried the maximum number of hashes without meeting the difficulty
+    #[error("no valid hash found after {attempts} attempts")]
+    MiningExhausted {
+        /// Number of hashes tried
+        attempts: u64,
+    },
+    /// The nonce reached `u64::MAX` without meeting the difficulty
+    #[error("nonce space exhausted without meeting the difficulty")]
+    NonceExhausted,
+    /// Mining was cancelled before a valid hash was found
+    #[error("mining was cancelled")]
+    Cancelled,
+    /// The name does not match any supported hash algorithm
+    #[error("unknown hash algorithm {0:?}")]
+    UnknownHashAlgo(String),
+    /// A submitted block does not extend the chain validly
+    #[error("block rejected: {0}")]
+    InvalidBlock(Tampering),
+    /// A chain file was saved in a format version this build cannot read or
+    /// migrate
+    #[error(
+        "unsupported chain file format version {0}; the latest is {latest}",
+        latest = crate::blockchain::FORMAT_VERSION
+    )]
+    UnsupportedVersion(u32),
+    /// A loaded chain failed integrity validation
+    #[error("chain failed validation")]
+    InvalidChain,
+    /// A [`Network`](crate::network::Network) has no node at the given index
+    #[error("no node at index {0}")]
+    UnknownNode(usize),
+}
@@ -0,0 +1,145 @@
+//! Hash algorithms a chain can use for block headers, transactions, and Merkle trees.
+use crate::blockchain::Chain;
+use crate::encode::Encode;
+use crate::error::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+use std::fmt;
+use std::str::FromStr;
+
+/// A 256-bit hash function selectable per chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HashAlgo {
+    /// SHA-256, the original algorithm of the sandbox
+    #[default]
+    Sha256,
+    /// SHA3-256 (Keccak)
+    Sha3_256,
+    /// BLAKE3 with a 32-byte output
+    Blake3,
+    /// SHA-256 applied twice, as in Bitcoin, which is immune to length
+    /// extension
+    Sha256d,
+}
+
+impl HashAlgo {
+    /// Hashes raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Data to hash
+    ///
+    /// # Returns
+    ///
+    /// The 32-byte digest
+    pub fn digest(self, bytes: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Sha256 => Sha256::digest(bytes).into(),
+            Self::Sha3_256 => Sha3_256::digest(bytes).into(),
+            Self::Blake3 => blake3::hash(bytes).into(),
+            Self::Sha256d => Sha256::digest(Sha256::digest(bytes)).into(),
+        }
+    }
+
+    /// Hashes the canonical encoding of an item.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Any encodable item to hash
+    ///
+    /// # Returns
+    ///
+    /// The 32-byte digest
+    pub fn digest_item<T: Encode + ?Sized>(self, item: &T) -> [u8; 32] {
+        self.digest(&item.to_bytes())
+    }
+
+    /// Computes the hash of an item.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Any encodable item to hash
+    ///
+    /// # Returns
+    ///
+    /// Hexadecimal string representation of the hash
+    pub fn hash<T: Encode + ?Sized>(self, item: &T) -> String {
+        Chain::hex_to_string(&self.digest_item(item))
+    }
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sha256 => "sha256",
+            Self::Sha3_256 => "sha3-256",
+            Self::Blake3 => "blake3",
+            Self::Sha256d => "sha256d",
+        })
+    }
+}
+
+impl FromStr for HashAlgo {
+    type Err = BlockchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(Self::Sha256),
+            "sha3" | "sha3-256" => Ok(Self::Sha3_256),
+            "blake3" => Ok(Self::Blake3),
+            "sha256d" | "sha-256d" => Ok(Self::Sha256d),
+            _ => Err(BlockchainError::UnknownHashAlgo(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BlockHeader, ZERO_HASH};
+
+    #[test]
+    fn algorithms_produce_distinct_digests() {
+        let digests =
+            [HashAlgo::Sha256, HashAlgo::Sha3_256, HashAlgo::Blake3].map(|a| a.digest(b"abc"));
+        assert_ne!(digests[0], digests[1]);
+        assert_ne!(digests[0], digests[2]);
+        assert_ne!(digests[1], digests[2]);
+    }
+
+    #[test]
+    fn sha256_matches_known_vector() {
+        assert_eq!(
+            Chain::hex_to_string(&HashAlgo::Sha256.digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn sha256d_hashes_header_twice() {
+        let header = BlockHeader::new(
+            chrono::DateTime::<chrono::Utc>::UNIX_EPOCH,
+            ZERO_HASH.to_string(),
+            ZERO_HASH.to_string(),
+            1,
+        );
+        let once = HashAlgo::Sha256.digest_item(&header);
+        let twice = HashAlgo::Sha256d.digest_item(&header);
+
+        assert_ne!(once, twice);
+        assert_eq!(twice, HashAlgo::Sha256.digest(&once));
+    }
+
+    #[test]
+    fn parses_algorithm_names() {
+        assert_eq!("SHA256".parse::<HashAlgo>().unwrap(), HashAlgo::Sha256);
+        assert_eq!("sha3".parse::<HashAlgo>().unwrap(), HashAlgo::Sha3_256);
+        assert_eq!(
+            HashAlgo::Blake3.to_string().parse::<HashAlgo>().unwrap(),
+            HashAlgo::Blake3
+        );
+        assert_eq!("sha256d".parse::<HashAlgo>().unwrap(), HashAlgo::Sha256d);
+        assert!("md5".parse::<HashAlgo>().is_err());
+    }
+}
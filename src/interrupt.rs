@@ -0,0 +1,46 @@
+//! Ctrl-C handling that cancels an in-progress mine instead of exiting.
+use blockchain_sandbox::blockchain::Chain;
+use blockchain_sandbox::error::BlockchainError;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while a block is being mined, so Ctrl-C cancels it instead of exiting.
+static MINING: AtomicBool = AtomicBool::new(false);
+
+/// Set by Ctrl-C to cancel the block being mined.
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+/// Installs the Ctrl-C handler.
+///
+/// While [`mine`] runs, Ctrl-C cancels the mining; otherwise the process exits
+/// with status 130 as it would without a handler.
+///
+/// # Returns
+///
+/// An error if a handler could not be registered
+pub(crate) fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if MINING.load(Ordering::SeqCst) {
+            CANCEL.store(true, Ordering::SeqCst);
+        } else {
+            std::process::exit(130);
+        }
+    })
+}
+
+/// Mines a new block that Ctrl-C can cancel.
+///
+/// # Arguments
+///
+/// * `chain` - Chain to mine the block on
+///
+/// # Returns
+///
+/// The result of [`Chain::generate_new_block_cancellable`], which is
+/// [`BlockchainError::Cancelled`] if Ctrl-C was pressed
+pub(crate) fn mine(chain: &mut Chain) -> Result<(), BlockchainError> {
+    CANCEL.store(false, Ordering::SeqCst);
+    MINING.store(true, Ordering::SeqCst);
+    let result = chain.generate_new_block_cancellable(&CANCEL);
+    MINING.store(false, Ordering::SeqCst);
+    result
+}
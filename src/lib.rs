@@ -0,0 +1,88 @@
+//! Blockchain logic of the sandbox: transactions, blocks, and a proof-of-work chain.
+//!
+//! The [`Chain`] type holds the blocks and the pending transaction pool, and
+//! mines new blocks from it.
+//!
+//! ```
+//! use blockchain_sandbox::Chain;
+//! use blockchain_sandbox::amount::COIN;
+//!
+//! let mut chain = Chain::new("miner".to_string(), 1, None)?;
+//! chain.add_transaction("miner".to_string(), "Bob".to_string(), 10 * COIN, None)?;
+//! chain.generate_new_block()?;
+//!
+//! assert_eq!(chain.len(), 2);
+//! assert_eq!(chain.get_balance("Bob"), 10 * COIN);
+//! # Ok::<(), blockchain_sandbox::error::BlockchainError>(())
+//! ```
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::all,
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::cargo,
+    missing_docs,
+    unreachable_pub,
+    unused_crate_dependencies
+)]
+#![warn(
+    rust_2018_idioms,
+    rust_2021_compatibility,
+    missing_debug_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+#![allow(
+    clippy::module_name_repetitions,
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::must_use_candidate,
+    clippy::doc_markdown,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss,
+    clippy::similar_names,
+    clippy::struct_excessive_bools,
+    clippy::multiple_crate_versions
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/t1ltxz-gxd/blockchain-sandbox/main/assets/images/logo.png"
+)]
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/t1ltxz-gxd/blockchain-sandbox/main/assets/images/favicon.png"
+)]
+
+pub mod address;
+pub mod amount;
+pub mod audit;
+pub mod blockchain;
+pub mod clock;
+pub mod config;
+pub mod consensus;
+pub mod encode;
+pub mod error;
+pub mod hash;
+pub mod network;
+pub mod script;
+pub mod storage;
+pub mod target;
+pub mod wallet;
+
+pub use blockchain::{Block, BlockHeader, Chain, OutPoint, Transaction, TxOut};
+pub use config::ChainConfig;
+pub use error::BlockchainError;
+pub use hash::HashAlgo;
+pub use network::Network;
+
+// Dependencies used only by the `blockchain-sandbox` binary.
+use clap as _;
+use colored as _;
+use ctrlc as _;
+use tracing_subscriber as _;
+#[cfg(feature = "server")]
+use {axum as _, tokio as _};
+// Dependencies used only by the benchmarks.
+#[cfg(test)]
+use criterion as _;
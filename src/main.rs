@@ -3,6 +3,10 @@
 //! - Add transactions to the blockchain
 //! - Adjust mining difficulty and rewards
 //! - View the current state of the blockchain
+//!
+//! Subcommands such as `mine` or `balance --address <ADDR>` run a single
+//! operation without the interactive menu. `--load <PATH>` starts the menu on a
+//! saved chain instead of a new one.
 #![forbid(unsafe_code)]
 #![deny(
     clippy::all,
@@ -10,8 +14,7 @@
     clippy::nursery,
     clippy::cargo,
     missing_docs,
-    unreachable_pub,
-    unused_crate_dependencies
+    unreachable_pub
 )]
 #![warn(
     rust_2018_idioms,
@@ -32,7 +35,8 @@
     clippy::cast_sign_loss,
     clippy::cast_precision_loss,
     clippy::similar_names,
-    clippy::struct_excessive_bools
+    clippy::struct_excessive_bools,
+    clippy::multiple_crate_versions
 )]
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/t1ltxz-gxd/blockchain-sandbox/main/assets/images/logo.png"
@@ -41,35 +45,118 @@
     html_favicon_url = "https://raw.githubusercontent.com/t1ltxz-gxd/blockchain-sandbox/main/assets/images/favicon.png"
 )]
 
+use blockchain_sandbox::amount::{format_amount, parse_amount};
+use blockchain_sandbox::{blockchain, error, wallet};
+use clap::Parser;
 use colored::Colorize;
-pub mod blockchain;
+use std::process::ExitCode;
+pub mod cli;
+pub mod interrupt;
+#[cfg(feature = "server")]
+pub mod server;
 
 /// Main entry point for the blockchain sandbox application.
 ///
+/// Runs the given subcommand, or starts the interactive menu when none is given.
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("off")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+    let cli = cli::Cli::parse();
+    if let Err(e) = interrupt::install() {
+        eprintln!("{}", format!("Ctrl-C will not cancel mining: {e}").yellow());
+    }
+    #[cfg(feature = "server")]
+    if let Some(port) = cli.serve {
+        return exit_code(cli::open(&cli).and_then(|chain| server::serve(chain, port)));
+    }
+    let Some(command) = &cli.command else {
+        return exit_code(interactive(cli.load.as_deref()));
+    };
+    exit_code(cli::run(&cli, command))
+}
+
+/// Converts the result of a command into the process exit code, printing any error.
+fn exit_code(result: Result<(), error::BlockchainError>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", format!("Error: {e}").red());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the interactive menu.
+///
+/// Loads the chain saved at `load`, or initializes a blockchain with
+/// user-specified miner address and difficulty, then presents an interactive
+/// menu for blockchain operations.
+///
+/// # Returns
+///
+/// The error of loading the chain, if it cannot be loaded, or of reading the
+/// settings of a new one
+fn interactive(load: Option<&std::path::Path>) -> Result<(), error::BlockchainError> {
+    let chain = match load {
+        Some(path) => {
+            let chain = blockchain::Chain::load_from_file(path)?;
+            println!(
+                "{}",
+                format!(
+                    "Chain loaded from {} at height {}",
+                    path.display(),
+                    chain.height()
+                )
+                .green()
+            );
+            chain
+        }
+        None => match new_chain()? {
+            Some(chain) => chain,
+            None => return Ok(()),
+        },
+    };
+    menu(chain);
+    Ok(())
+}
+
 /// Initializes a blockchain with user-specified miner address and difficulty,
-/// then presents an interactive menu for blockchain operations.
-fn main() {
-    let mut miner_address = String::new();
-    println!("Enter miner address: ");
-    std::io::stdin()
-        .read_line(&mut miner_address)
-        .expect("Failed to read line");
-    let miner_address = miner_address.trim().to_string();
-
-    let mut difficulty = String::new();
-    println!("Enter difficulty (default 2): ");
-    std::io::stdin()
-        .read_line(&mut difficulty)
-        .expect("Failed to read line");
-    let difficulty: u32 = difficulty.trim().parse().unwrap_or(2);
+/// printing its genesis block.
+///
+/// # Returns
+///
+/// The new chain, None if it could not be created, or the error reading the
+/// settings from stdin
+fn new_chain() -> std::io::Result<Option<blockchain::Chain>> {
+    let miner_address = prompt("Enter miner address: ")?;
+    let difficulty: u32 = prompt("Enter difficulty (default 2): ")?
+        .parse()
+        .unwrap_or(2);
 
     println!("Generating genesis block...");
-    let mut chain = blockchain::Chain::new(miner_address, difficulty, None);
+    let chain = match blockchain::Chain::new(miner_address, difficulty, None) {
+        Ok(chain) => chain,
+        Err(e) => {
+            println!("{}", format!("Failed to create chain: {e}").red());
+            return Ok(None);
+        }
+    };
 
-    if let Some(genesis) = chain.get_latest_block_json() {
-        println!("Genesis Block:\n{}", genesis.green());
+    match chain.get_latest_block_json() {
+        Ok(Some(genesis)) => println!("Genesis Block:\n{}", genesis.green()),
+        Ok(None) => {}
+        Err(e) => println!("{}", format!("Failed to display genesis block: {e}").red()),
     }
+    Ok(Some(chain))
+}
 
+/// Presents the interactive menu for operations on `chain` until the user exits.
+fn menu(mut chain: blockchain::Chain) {
     loop {
         println!();
         println!("{}", "Choose an option:".blue().bold());
@@ -78,17 +165,54 @@ fn main() {
         println!("{}", "3. Change difficulty".yellow());
         println!("{}", "4. Change reward".cyan());
         println!("{}", "5. Show blockchain".white());
+        println!("{}", "6. Save".blue());
+        println!("{}", "7. Load".blue());
+        println!("{}", "8. Show balance".cyan());
+        println!("{}", "9. Create wallet".magenta());
+        println!("{}", "10. Show block".white());
+        println!("{}", "11. Change halving interval".cyan());
+        println!("{}", "12. Configure difficulty retarget".yellow());
+        println!("{}", "13. Change mempool limit".magenta());
+        println!("{}", "14. Change block capacity".magenta());
+        println!("{}", "15. Verify transaction inclusion".cyan());
+        println!("{}", "16. Change mining iteration limit".yellow());
+        println!("{}", "17. Change mining threads".yellow());
+        println!("{}", "18. Export transactions to CSV".blue());
+        println!("{}", "19. List mempool".magenta());
+        println!("{}", "20. Remove pending transaction".magenta());
+        println!("{}", "21. Adopt a heavier chain from a file".blue());
         println!("{}", "0. Exit".red().underline());
 
+        let choice = read_choice();
+        if let Err(e) = handle_menu_choice(&mut chain, &choice) {
+            println!("{}", format!("Failed to read input: {e}").red());
+            break;
+        }
+        if choice == "0" {
+            break;
+        }
+    }
+}
+
+/// Prompts for a menu choice until a non-blank one is entered.
+///
+/// # Returns
+///
+/// The trimmed choice, or "0" to exit once stdin is closed or unreadable
+fn read_choice() -> String {
+    loop {
         print!("Enter your choice: ");
         let mut choice = String::new();
-        std::io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read line");
+        match std::io::stdin().read_line(&mut choice) {
+            Ok(0) | Err(_) => {
+                println!();
+                return "0".to_string();
+            }
+            Ok(_) => {}
+        }
         let choice = choice.trim();
-        handle_menu_choice(&mut chain, choice);
-        if choice == "0" {
-            break;
+        if !choice.is_empty() {
+            return choice.to_string();
         }
     }
 }
@@ -107,102 +231,524 @@ fn main() {
 /// * "3": Change the mining difficulty
 /// * "4": Change the mining reward
 /// * "5": Display the entire blockchain
+/// * "6": Save the blockchain to a file
+/// * "7": Load the blockchain from a file
+/// * "8": Show the balance and history of an address
+/// * "9": Create a new wallet
+/// * "10": Display a single block by index or hash
+/// * "11": Change the reward halving interval
+/// * "12": Configure automatic difficulty retargeting
+/// * "13": Change the maximum number of pending transactions
+/// * "14": Change the maximum number of transactions per block
+/// * "15": Verify a transaction's Merkle inclusion proof
+/// * "16": Change the maximum number of hashes tried per block
+/// * "17": Change the number of mining threads
+/// * "18": Export all confirmed transactions to a CSV file
+/// * "19": List pending transactions with their indices
+/// * "20": Remove a pending transaction by index
+/// * "21": Replace the chain with a valid chain holding more work, loaded from a file
 /// * "0": Exit the application
-fn handle_menu_choice(chain: &mut blockchain::Chain, choice: &str) {
+///
+/// # Returns
+///
+/// `Ok(())` once the choice is handled, or the error reading one of its
+/// prompts from stdin
+fn handle_menu_choice(chain: &mut blockchain::Chain, choice: &str) -> std::io::Result<()> {
     match choice {
-        "1" => {
-            let mut sender = String::new();
-            let mut receiver = String::new();
-            let mut amount = String::new();
-
-            println!("Sender: ");
-            std::io::stdin().read_line(&mut sender).unwrap();
-            println!("Receiver: ");
-            std::io::stdin().read_line(&mut receiver).unwrap();
-            println!("Amount: ");
-            std::io::stdin().read_line(&mut amount).unwrap();
-
-            let sender = sender.trim();
-            let receiver = receiver.trim();
-            let amount: f32 = amount.trim().parse().unwrap_or(0.0);
-
-            if chain.add_transaction(sender.to_string(), receiver.to_string(), amount) {
-                println!("{}", "Transaction added successfully:".green().bold());
-                println!("From: {sender}");
-                println!("To: {receiver}");
-                println!("Amount: {amount}");
-            } else {
-                println!("{}", "Failed to add transaction.".red());
-            }
+        "1" => new_transaction(chain)?,
+
+        "2" => mine_block(chain),
+
+        "3" => change_difficulty(chain)?,
+
+        "4" => change_reward(chain)?,
+
+        "5" => show_blockchain(chain),
+
+        "6" => save_chain(chain)?,
+
+        "7" => load_chain(chain)?,
+
+        "8" => show_balance(chain)?,
+
+        "9" => create_wallet(),
+
+        "10" => show_block(chain)?,
+
+        "11" => change_halving_interval(chain)?,
+
+        "12" => configure_retarget(chain)?,
+
+        "13" => change_max_mempool(chain)?,
+
+        "14" => change_max_block_txs(chain)?,
+
+        "15" => verify_inclusion(chain)?,
+
+        "16" => change_max_mining_iterations(chain)?,
+
+        "17" => change_mining_threads(chain)?,
+
+        "18" => export_csv(chain)?,
+
+        "19" => list_mempool(chain),
+
+        "20" => remove_pending(chain)?,
+
+        "21" => adopt_chain(chain)?,
+
+        "0" => {
+            println!("{}", "Exiting program.".red().bold());
         }
 
-        "2" => {
-            println!("{}", "Mining new block...".yellow().bold());
+        _ => println!("{}", "Invalid choice, try again.".red()),
+    }
+    Ok(())
+}
 
-            chain.generate_new_block();
+/// Prints a prompt and reads a trimmed line from stdin.
+///
+/// # Returns
+///
+/// The trimmed line, empty once stdin is closed, or the error reading it
+fn prompt(message: &str) -> std::io::Result<String> {
+    let mut input = String::new();
+    println!("{message}");
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
 
-            let latest_block_after = chain.get_chain().last().unwrap();
-            let block_hash = blockchain::Chain::hash(&latest_block_after.get_header());
-            println!("{}", "New block mined:".green().bold());
-            println!("Hash:         {block_hash}");
-            println!(
-                "Prev Hash:    {}",
-                latest_block_after.get_header().get_previous_hash()
-            );
-            println!(
-                "Nonce:        {}",
-                latest_block_after.get_header().get_nonce()
-            );
-            println!(
-                "Transactions: {}",
-                latest_block_after.get_transactions().len()
-            );
-            println!("Reward:       {}", chain.get_reward());
+/// Adds a user-specified transaction to the pending transactions pool.
+fn new_transaction(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let sender = prompt("Sender: ")?;
+    let receiver = prompt("Receiver: ")?;
+    let amount = parse_amount(&prompt("Amount: ")?).unwrap_or(0);
+    if !blockchain::Chain::is_valid_amount(amount) {
+        println!(
+            "{}",
+            "Transaction rejected: amount must be a positive number.".red()
+        );
+        return Ok(());
+    }
+    let fee = match prompt("Fee (default 0): ")?.as_str() {
+        "" => Some(0),
+        fee => parse_amount(fee),
+    };
+    let Some(fee) = fee else {
+        println!(
+            "{}",
+            "Transaction rejected: fee must be a non-negative number.".red()
+        );
+        return Ok(());
+    };
+
+    let memo = prompt("Memo (leave empty for none): ")?;
+
+    let mut transaction = blockchain::Transaction::new(sender.clone(), receiver.clone(), amount)
+        .with_fee(fee)
+        .with_timestamp(chrono::Utc::now());
+    if !memo.is_empty() {
+        transaction = transaction.with_memo(memo.clone());
+    }
+    let signing_key = prompt("Signing key (hex, leave empty for unsigned): ")?;
+    let added = if signing_key.is_empty() {
+        chain.submit_transaction(transaction)
+    } else {
+        let Some(wallet) = wallet::Wallet::from_secret_hex(&signing_key) else {
+            println!("{}", "Transaction rejected: invalid signing key.".red());
+            return Ok(());
+        };
+        transaction
+            .sign(&wallet)
+            .and_then(|()| chain.add_signed_transaction(transaction))
+    };
+
+    match added {
+        Ok(()) => {
+            println!("{}", "Transaction added successfully:".green().bold());
+            println!("From: {sender}");
+            println!("To: {receiver}");
+            println!("Amount: {}", format_amount(amount));
+            println!("Fee: {}", format_amount(fee));
+            if !memo.is_empty() {
+                println!("Memo: {memo}");
+            }
         }
+        Err(e) => println!("{}", format!("Transaction rejected: {e}").red()),
+    }
+    Ok(())
+}
+
+/// Mines a new block with pending transactions and prints a summary.
+fn mine_block(chain: &mut blockchain::Chain) {
+    println!("{}", "Mining new block...".yellow().bold());
+
+    println!("Press Ctrl-C to cancel.");
+    if let Err(e) = interrupt::mine(chain) {
+        println!("{}", format!("Mining failed: {e}").red());
+        return;
+    }
+
+    let Some(latest_block_after) = chain.latest() else {
+        return;
+    };
+    println!("{}", "New block mined:".green().bold());
+    println!("Hash:         {}", latest_block_after.get_hash());
+    println!(
+        "Prev Hash:    {}",
+        latest_block_after.get_header().get_previous_hash()
+    );
+    println!(
+        "Nonce:        {}",
+        latest_block_after.get_header().get_nonce()
+    );
+    println!(
+        "Transactions: {}",
+        latest_block_after.get_transactions().len()
+    );
+    println!(
+        "Reward:       {}",
+        format_amount(latest_block_after.get_transactions()[0].amount)
+    );
+}
 
-        "3" => {
-            let mut new_difficulty = String::new();
-            println!("Enter new difficulty: ");
-            std::io::stdin().read_line(&mut new_difficulty).unwrap();
-            let new_difficulty: u32 = new_difficulty
-                .trim()
-                .parse()
-                .unwrap_or_else(|_| chain.get_difficulty());
-
-            let old_difficulty = chain.get_difficulty();
-            chain.update_difficulty(new_difficulty);
+/// Changes the mining difficulty to a user-specified value.
+fn change_difficulty(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_difficulty: u32 = prompt("Enter new difficulty: ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_difficulty());
+
+    let old_difficulty = chain.get_difficulty();
+    match chain.update_difficulty(new_difficulty) {
+        Ok(()) => {
             println!("{}", "Difficulty updated:".cyan().bold());
             println!("Old: {old_difficulty}");
             println!("New: {new_difficulty}");
         }
+        Err(e) => println!("{}", format!("Difficulty not updated: {e}").red()),
+    }
+    Ok(())
+}
+
+/// Changes the mining reward to a user-specified value.
+fn change_reward(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_reward =
+        parse_amount(&prompt("Enter new reward: ")?).unwrap_or_else(|| chain.get_reward());
+
+    let old_reward = chain.get_reward();
+    chain.update_reward(new_reward);
+    println!("{}", "Reward updated:".cyan().bold());
+    println!("  Old: {}", format_amount(old_reward));
+    println!("  New: {}", format_amount(new_reward));
+    Ok(())
+}
+
+/// Changes the reward halving interval to a user-specified value.
+fn change_halving_interval(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_interval: u64 = prompt("Enter new halving interval (blocks): ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_halving_interval());
 
-        "4" => {
-            let mut new_reward = String::new();
-            println!("Enter new reward: ");
-            std::io::stdin().read_line(&mut new_reward).unwrap();
-            let new_reward: f32 = new_reward
-                .trim()
-                .parse()
-                .unwrap_or_else(|_| chain.get_reward());
-
-            let old_reward = chain.get_reward();
-            chain.update_reward(new_reward);
-            println!("{}", "Reward updated:".cyan().bold());
-            println!("  Old: {old_reward}");
-            println!("  New: {new_reward}");
+    let old_interval = chain.get_halving_interval();
+    if chain.update_halving_interval(new_interval) {
+        println!("{}", "Halving interval updated:".cyan().bold());
+        println!("  Old: {old_interval}");
+        println!("  New: {new_interval}");
+    } else {
+        println!("{}", "Halving interval must be at least 1.".red());
+    }
+    Ok(())
+}
+
+/// Configures automatic difficulty retargeting from user input.
+fn configure_retarget(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let interval: u64 = prompt("Retarget interval in blocks (0 disables): ")?
+        .parse()
+        .unwrap_or(0);
+    let target_secs: u64 = prompt("Target block time in seconds (default 10): ")?
+        .parse()
+        .unwrap_or(10);
+
+    if chain.update_retarget(interval, std::time::Duration::from_secs(target_secs)) {
+        println!("{}", "Difficulty retarget updated:".yellow().bold());
+        println!("  Interval:          {interval}");
+        println!("  Target block time: {target_secs}s");
+    } else {
+        println!("{}", "Target block time must be at least 1 second.".red());
+    }
+    Ok(())
+}
+
+/// Changes the mempool capacity to a user-specified value.
+fn change_max_mempool(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_max: usize = prompt("Enter new mempool limit: ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_max_mempool());
+
+    let old_max = chain.get_max_mempool();
+    if chain.update_max_mempool(new_max) {
+        println!("{}", "Mempool limit updated:".magenta().bold());
+        println!("  Old: {old_max}");
+        println!("  New: {new_max}");
+    } else {
+        println!("{}", "Mempool limit must be at least 1.".red());
+    }
+    Ok(())
+}
+
+/// Changes the block capacity to a user-specified value.
+fn change_max_block_txs(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_max: usize = prompt("Enter new maximum transactions per block: ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_max_block_txs());
+
+    let old_max = chain.get_max_block_txs();
+    if chain.update_max_block_txs(new_max) {
+        println!("{}", "Block capacity updated:".magenta().bold());
+        println!("  Old: {old_max}");
+        println!("  New: {new_max}");
+    } else {
+        println!("{}", "Block capacity must be at least 1.".red());
+    }
+    Ok(())
+}
+
+/// Changes the mining iteration limit to a user-specified value.
+fn change_max_mining_iterations(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_max: u64 = prompt("Enter new maximum hashes per block: ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_max_mining_iterations());
+
+    let old_max = chain.get_max_mining_iterations();
+    if chain.update_max_mining_iterations(new_max) {
+        println!("{}", "Mining iteration limit updated:".yellow().bold());
+        println!("  Old: {old_max}");
+        println!("  New: {new_max}");
+    } else {
+        println!("{}", "Mining iteration limit must be at least 1.".red());
+    }
+    Ok(())
+}
+
+/// Changes the number of mining threads to a user-specified value.
+fn change_mining_threads(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let new_threads: usize = prompt("Enter new number of mining threads: ")?
+        .parse()
+        .unwrap_or_else(|_| chain.get_mining_threads());
+
+    let old_threads = chain.get_mining_threads();
+    if chain.update_mining_threads(new_threads) {
+        println!("{}", "Mining threads updated:".yellow().bold());
+        println!("  Old: {old_threads}");
+        println!("  New: {new_threads}");
+    } else {
+        println!("{}", "Mining threads must be at least 1.".red());
+    }
+    Ok(())
+}
+
+/// Prints a summary of every block and its transactions along with the chain's
+/// validity.
+fn show_blockchain(chain: &blockchain::Chain) {
+    println!("{}", "Current blockchain:".bold());
+    for block in chain {
+        println!("{block}");
+        for transaction in block.get_transactions() {
+            println!("  {transaction}");
         }
+    }
+    println!("Total supply: {}", format_amount(chain.total_supply()));
+    if chain.get_burn_address().is_some() {
+        println!(
+            "Circulating supply: {}",
+            format_amount(chain.circulating_supply())
+        );
+    }
+    match chain.find_tampering() {
+        None => println!("{}", "Chain is valid.".green()),
+        Some(tampering) => println!(
+            "{}",
+            format!("Chain has been tampered with at {tampering}!")
+                .red()
+                .bold()
+        ),
+    }
+}
 
-        "5" => {
-            println!("{}", "Current blockchain:".bold());
-            for (i, block) in chain.get_blocks_json().iter().enumerate() {
-                println!("--- Block #{i} ---\n{block}\n");
-            }
+/// Prints the block at a user-specified index or with a user-specified hash.
+fn show_block(chain: &blockchain::Chain) -> std::io::Result<()> {
+    let query = prompt("Block index or hash: ")?;
+    let block = query.parse::<u64>().map_or_else(
+        |_| chain.get_block_by_hash(&query),
+        |index| chain.get_block_by_index(index),
+    );
+    match block.map(serde_json::to_string_pretty) {
+        Some(Ok(json)) => println!("{json}"),
+        Some(Err(e)) => println!("{}", format!("Failed to display block: {e}").red()),
+        None => println!("{}", format!("No block found for {query}.").red()),
+    }
+    Ok(())
+}
+
+/// Builds and checks a Merkle inclusion proof for a transaction in a block.
+fn verify_inclusion(chain: &blockchain::Chain) -> std::io::Result<()> {
+    let Some(block) = prompt("Block index: ")?
+        .parse::<u64>()
+        .ok()
+        .and_then(|index| chain.get_block_by_index(index))
+    else {
+        println!("{}", "No such block.".red());
+        return Ok(());
+    };
+    let transactions = block.get_transactions();
+    let Some((position, transaction)) = prompt("Transaction position: ")?
+        .parse::<usize>()
+        .ok()
+        .and_then(|position| transactions.get(position).map(|t| (position, t)))
+    else {
+        println!("{}", "No such transaction in this block.".red());
+        return Ok(());
+    };
+
+    let algo = chain.get_hash_algo();
+    let proof = blockchain::Chain::merkle_proof(transactions, position, algo);
+    let tx_hash = algo.hash(transaction);
+    for (sibling, is_left) in &proof {
+        let side = if *is_left { "left" } else { "right" };
+        println!("  {side}: {sibling}");
+    }
+    let root = block.get_header().get_merkle();
+    if blockchain::Chain::verify_merkle_proof(&tx_hash, &proof, &root, algo) {
+        println!("{}", "Transaction is included in the block.".green());
+    } else {
+        println!(
+            "{}",
+            "Inclusion proof does not match the Merkle root.".red()
+        );
+    }
+    Ok(())
+}
+
+/// Prompts for a chain file path, defaulting to `chain.json`.
+fn read_path() -> std::io::Result<std::path::PathBuf> {
+    let path = prompt("File path (default chain.json): ")?;
+    Ok(if path.is_empty() {
+        "chain.json".into()
+    } else {
+        path.into()
+    })
+}
+
+/// Saves the chain to a user-specified file.
+fn save_chain(chain: &blockchain::Chain) -> std::io::Result<()> {
+    let path = read_path()?;
+    match chain.save_to_file(&path) {
+        Ok(()) => println!("{}", format!("Chain saved to {}", path.display()).green()),
+        Err(e) => println!("{}", format!("Failed to save chain: {e}").red()),
+    }
+    Ok(())
+}
+
+/// Exports the chain's transactions to a user-specified CSV file.
+fn export_csv(chain: &blockchain::Chain) -> std::io::Result<()> {
+    let path = prompt("CSV file path (default chain.csv): ")?;
+    let path = std::path::PathBuf::from(if path.is_empty() { "chain.csv" } else { &path });
+    match chain.export_csv(&path) {
+        Ok(()) => println!(
+            "{}",
+            format!("Transactions exported to {}", path.display()).green()
+        ),
+        Err(e) => println!("{}", format!("Failed to export transactions: {e}").red()),
+    }
+    Ok(())
+}
+
+/// Replaces the chain with one loaded from a user-specified file.
+fn load_chain(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let path = read_path()?;
+    match blockchain::Chain::load_from_file(&path) {
+        Ok(loaded) => {
+            *chain = loaded;
+            println!(
+                "{}",
+                format!("Chain loaded from {}", path.display()).green()
+            );
         }
+        Err(e) => println!("{}", format!("Failed to load chain: {e}").red()),
+    }
+    Ok(())
+}
 
-        "0" => {
-            println!("{}", "Exiting program.".red().bold());
+/// Prints the confirmed balance and transaction history of a user-specified address.
+fn show_balance(chain: &blockchain::Chain) -> std::io::Result<()> {
+    let address = prompt("Address: ")?;
+    println!(
+        "Balance of {address}: {}",
+        format_amount(chain.get_balance(&address))
+    );
+    for (index, transaction) in chain.find_transactions(&address) {
+        println!("  Block #{index}: {transaction}");
+    }
+    Ok(())
+}
+
+/// Replaces the chain with one holding more work, loaded from a user-specified file.
+fn adopt_chain(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let path = read_path()?;
+    let candidate = match blockchain::Chain::load_from_file(&path) {
+        Ok(candidate) => candidate,
+        Err(e) => {
+            println!("{}", format!("Failed to load chain: {e}").red());
+            return Ok(());
         }
+    };
+    if chain.replace_chain(candidate.get_chain().clone()) {
+        println!(
+            "{}",
+            format!("Adopted chain of {} blocks", chain.len()).green()
+        );
+    } else {
+        println!(
+            "{}",
+            "Kept the current chain; the other one has less work or is not valid.".yellow()
+        );
+    }
+    Ok(())
+}
 
-        _ => println!("{}", "Invalid choice, try again.".red()),
+/// Prints the pending transactions with their indices.
+fn list_mempool(chain: &blockchain::Chain) {
+    let pending = chain.pending_transactions();
+    if pending.is_empty() {
+        println!("{}", "Mempool is empty.".yellow());
+        return;
     }
+    println!(
+        "{}",
+        format!("Pending transactions ({}):", pending.len()).bold()
+    );
+    for (index, transaction) in pending.iter().enumerate() {
+        println!("  [{index}] {transaction}");
+    }
+}
+
+/// Removes a user-specified pending transaction.
+fn remove_pending(chain: &mut blockchain::Chain) -> std::io::Result<()> {
+    let removed = prompt("Index of the pending transaction to remove: ")?
+        .parse()
+        .ok()
+        .and_then(|index| chain.remove_pending(index));
+    match removed {
+        Some(transaction) => println!("{}", format!("Removed {transaction}").green()),
+        None => println!("{}", "No pending transaction at that index.".red()),
+    }
+    Ok(())
+}
+
+/// Generates a new wallet and prints its address and signing key.
+fn create_wallet() {
+    let wallet = wallet::Wallet::new();
+    println!("{}", "New wallet created:".green().bold());
+    println!("Address:     {}", wallet.address());
+    println!("Signing key: {}", wallet.secret_hex());
 }
@@ -0,0 +1,159 @@
+//! In-process network of chains, for studying how nodes reach consensus.
+//!
+//! Nodes are plain [`Chain`]s that exchange blocks through method calls; no
+//! sockets are involved.
+use crate::blockchain::{Block, Chain};
+use crate::error::BlockchainError;
+
+/// A set of nodes, each holding its own copy of the chain.
+///
+/// Nodes should share a genesis block, for example by creating them with
+/// [`Chain::with_genesis_timestamp`] and the same arguments, so that their
+/// chains can be compared.
+#[derive(Debug)]
+pub struct Network {
+    /// Chains of the nodes, indexed by node
+    nodes: Vec<Chain>,
+}
+
+impl Network {
+    /// Creates a network of the given nodes.
+    pub const fn new(nodes: Vec<Chain>) -> Self {
+        Self { nodes }
+    }
+
+    /// Returns the chains of all nodes.
+    pub fn nodes(&self) -> &[Chain] {
+        &self.nodes
+    }
+
+    /// Returns the chain of a node for direct changes, such as adding
+    /// transactions, or None if there is no node at `index`.
+    pub fn node_mut(&mut self, index: usize) -> Option<&mut Chain> {
+        self.nodes.get_mut(index)
+    }
+
+    /// Mines a block on one node without telling the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_index` - Index of the mining node
+    ///
+    /// # Returns
+    ///
+    /// A copy of the mined block, ready for [`Network::broadcast_block`],
+    /// [`BlockchainError::UnknownNode`] if there is no such node, or the error
+    /// of [`Chain::generate_new_block`]
+    pub fn mine_on(&mut self, node_index: usize) -> Result<Block, BlockchainError> {
+        let node = self
+            .nodes
+            .get_mut(node_index)
+            .ok_or(BlockchainError::UnknownNode(node_index))?;
+        node.generate_new_block()?;
+        node.latest()
+            .cloned()
+            .ok_or(BlockchainError::UnknownNode(node_index))
+    }
+
+    /// Delivers a block to every node that does not have it yet.
+    ///
+    /// Each node first tries to append the block with [`Chain::submit_block`].
+    /// A node on a different branch cannot, so it is offered the chain of a node
+    /// holding the block, up to that block, and reorganizes onto it if
+    /// [`Chain::replace_chain`] finds it carries more work.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block mined on one of the nodes
+    ///
+    /// # Returns
+    ///
+    /// Number of nodes that appended the block or reorganized onto its chain
+    pub fn broadcast_block(&mut self, block: &Block) -> usize {
+        let branch = self.nodes.iter().find_map(|node| {
+            let position = node.iter().position(|b| b == block)?;
+            Some(node.get_chain()[..=position].to_vec())
+        });
+        let mut adopted = 0;
+        for node in &mut self.nodes {
+            if node.get_chain().contains(block) {
+                continue;
+            }
+            if node.submit_block(block.clone()).is_ok()
+                || branch
+                    .as_ref()
+                    .is_some_and(|branch| node.replace_chain(branch.clone()))
+            {
+                adopted += 1;
+            }
+        }
+        tracing::debug!(index = block.get_index(), adopted, "block broadcast");
+        adopted
+    }
+
+    /// Returns `true` if every node has the same chain.
+    pub fn is_converged(&self) -> bool {
+        self.nodes
+            .windows(2)
+            .all(|pair| pair[0].get_chain() == pair[1].get_chain())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::COIN;
+    use chrono::{DateTime, Utc};
+
+    fn network(size: usize) -> Network {
+        let genesis = DateTime::parse_from_rfc3339("2009-01-03T18:15:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Network::new(
+            (0..size)
+                .map(|_| {
+                    Chain::with_genesis_timestamp("Tilt".to_string(), 1, None, genesis).unwrap()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn broadcast_block_extends_every_node() {
+        let mut network = network(3);
+        let block = network.mine_on(0).unwrap();
+
+        assert_eq!(network.broadcast_block(&block), 2);
+        assert!(network.is_converged());
+        assert_eq!(network.nodes()[2].len(), 2);
+    }
+
+    #[test]
+    fn competing_nodes_converge_on_longer_chain() {
+        let mut network = network(2);
+        network
+            .node_mut(1)
+            .unwrap()
+            .add_transaction("Tilt".to_string(), "Bob".to_string(), COIN, None)
+            .unwrap();
+        let short = network.mine_on(0).unwrap();
+        network.mine_on(1).unwrap();
+        let long = network.mine_on(1).unwrap();
+        assert!(!network.is_converged());
+
+        assert_eq!(network.broadcast_block(&short), 0);
+        assert_eq!(network.broadcast_block(&long), 1);
+        assert!(network.is_converged());
+        assert_eq!(network.nodes()[0].len(), 3);
+        assert_eq!(network.nodes()[0].get_balance("Bob"), COIN);
+    }
+
+    #[test]
+    fn mine_on_rejects_unknown_node() {
+        let mut network = network(1);
+        assert!(matches!(
+            network.mine_on(1),
+            Err(BlockchainError::UnknownNode(1))
+        ));
+    }
+}
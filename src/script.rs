@@ -0,0 +1,139 @@
+//! A tiny script language locking transaction outputs, for experimenting with
+//! programmable spending conditions.
+//!
+//! A [`TxOut`](crate::TxOut) may carry a script that every transaction spending
+//! it must satisfy. Scripts run on a stack of byte strings and succeed if they
+//! end with a non-zero value on top. The opcodes are:
+//!
+//! * `0x01..=0x4b` - pushes that many following bytes
+//! * [`OP_RETURN`] - fails at once, so the output can never be spent; the
+//!   bytes after it carry arbitrary data
+//! * [`OP_CHECKSIG`] - pops a 32-byte Ed25519 public key and fails unless the
+//!   spending transaction is signed by it, then pushes `1`
+use crate::blockchain::Transaction;
+use crate::error::BlockchainError;
+
+/// Marks an output as unspendable, carrying the data pushed after it.
+pub const OP_RETURN: u8 = 0x6a;
+
+/// Checks the spending transaction's signature against the public key on top
+/// of the stack.
+pub const OP_CHECKSIG: u8 = 0xac;
+
+/// Longest data a single push opcode can push.
+const MAX_PUSH: usize = 0x4b;
+
+/// Builds a script making an output unspendable and carrying `data`.
+pub fn op_return(data: &[u8]) -> Vec<u8> {
+    let mut script = vec![OP_RETURN];
+    for chunk in data.chunks(MAX_PUSH) {
+        script.push(chunk.len() as u8);
+        script.extend_from_slice(chunk);
+    }
+    script
+}
+
+/// Builds a script letting only transactions signed by `public_key` spend an
+/// output.
+pub fn pay_to_public_key(public_key: &[u8; 32]) -> Vec<u8> {
+    let mut script = vec![public_key.len() as u8];
+    script.extend_from_slice(public_key);
+    script.push(OP_CHECKSIG);
+    script
+}
+
+/// Checks whether a script fails before reading anything, as one built with
+/// [`op_return`].
+pub fn is_unspendable(script: &[u8]) -> bool {
+    script.first() == Some(&OP_RETURN)
+}
+
+/// Runs a script against a transaction spending the output it locks.
+///
+/// # Arguments
+///
+/// * `script` - Script of the spent output
+/// * `spender` - Transaction spending the output
+///
+/// # Returns
+///
+/// `Ok(())` if the script succeeds, [`BlockchainError::InvalidSignature`] if
+/// an [`OP_CHECKSIG`] finds no valid signature, or
+/// [`BlockchainError::InvalidScript`] if it reaches [`OP_RETURN`], is
+/// malformed, or ends without a non-zero value on top
+pub fn execute(script: &[u8], spender: &Transaction) -> Result<(), BlockchainError> {
+    let mut stack: Vec<&[u8]> = Vec::new();
+    let mut rest = script;
+    while let Some((&op, after)) = rest.split_first() {
+        rest = after;
+        match op {
+            0x01..=0x4b => {
+                let (data, after) = rest
+                    .split_at_checked(usize::from(op))
+                    .ok_or(BlockchainError::InvalidScript)?;
+                stack.push(data);
+                rest = after;
+            }
+            OP_CHECKSIG => {
+                let key = stack
+                    .pop()
+                    .and_then(|key| <[u8; 32]>::try_from(key).ok())
+                    .ok_or(BlockchainError::InvalidScript)?;
+                if !spender.verify_with_key(&key) {
+                    return Err(BlockchainError::InvalidSignature);
+                }
+                stack.push(&[1]);
+            }
+            _ => return Err(BlockchainError::InvalidScript),
+        }
+    }
+    if stack
+        .last()
+        .is_some_and(|top| top.iter().any(|&byte| byte != 0))
+    {
+        Ok(())
+    } else {
+        Err(BlockchainError::InvalidScript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn op_return_never_succeeds() {
+        let script = op_return(&[7; 100]);
+        assert!(is_unspendable(&script));
+        assert!(matches!(
+            execute(
+                &script,
+                &Transaction::new("a".to_string(), "b".to_string(), 1)
+            ),
+            Err(BlockchainError::InvalidScript)
+        ));
+    }
+
+    #[test]
+    fn checksig_needs_signature_by_the_key() {
+        let wallet = Wallet::new();
+        let script = pay_to_public_key(&wallet.public_key());
+        let mut tx = Transaction::new(wallet.address(), "Bob".to_string(), 1000);
+        assert!(matches!(
+            execute(&script, &tx),
+            Err(BlockchainError::InvalidSignature)
+        ));
+
+        tx.sign(&wallet).unwrap();
+        execute(&script, &tx).unwrap();
+        assert!(matches!(
+            execute(&pay_to_public_key(&Wallet::new().public_key()), &tx),
+            Err(BlockchainError::InvalidSignature)
+        ));
+        assert!(matches!(
+            execute(&script[..10], &tx),
+            Err(BlockchainError::InvalidScript)
+        ));
+    }
+}
@@ -0,0 +1,260 @@
+//! Minimal REST API over a chain, enabled with the `server` feature.
+//!
+//! Routes:
+//!
+//! * `GET /blocks` - every block of the chain
+//! * `GET /blocks/{index}` - a single block, or 404 if there is none at `index`
+//! * `GET /balance/{address}` - the confirmed balance of an address
+//! * `POST /tx` - adds a pending transaction from a `{sender, receiver, amount}` body
+//! * `POST /mine` - mines a block paying the chain's miner and returns it
+//! * `GET /metrics` - chain statistics in the Prometheus text format
+//!
+//! Amounts and balances are integers in base units.
+use axum::extract::{Path, State};
+use axum::http::{StatusCode, header};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use blockchain_sandbox::blockchain::{Block, Chain};
+use blockchain_sandbox::error::BlockchainError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Chain shared between request handlers.
+type SharedChain = Arc<Served>;
+
+/// Chain being served, with what the metrics need to know about its start.
+#[derive(Debug)]
+pub(crate) struct Served {
+    /// Chain the handlers read from and add to
+    chain: Mutex<Chain>,
+    /// Number of blocks the chain had when serving started
+    start_len: usize,
+}
+
+impl Served {
+    /// Wraps a chain for serving, taking its current length as the start.
+    pub(crate) fn new(chain: Chain) -> SharedChain {
+        let start_len = chain.len();
+        Arc::new(Self {
+            chain: Mutex::new(chain),
+            start_len,
+        })
+    }
+}
+
+/// Error response carrying a status code and a `{"error": ...}` body.
+type ApiError = (StatusCode, Json<Value>);
+
+/// Body of a `POST /tx` request.
+#[derive(Debug, Serialize, Deserialize)]
+struct TxRequest {
+    /// Address of the sender
+    sender: String,
+    /// Address of the receiver
+    receiver: String,
+    /// Amount to transfer, in base units
+    amount: u64,
+}
+
+/// Body of a `GET /balance/{address}` response.
+#[derive(Debug, Serialize)]
+struct BalanceResponse {
+    /// Address the balance was computed for
+    address: String,
+    /// Confirmed balance of the address, in base units
+    balance: u64,
+}
+
+/// Builds the router serving the API routes over a shared chain.
+///
+/// # Arguments
+///
+/// * `chain` - Chain the handlers read from and add transactions to
+///
+/// # Returns
+///
+/// A router with all API routes
+pub(crate) fn router(chain: SharedChain) -> Router {
+    Router::new()
+        .route("/blocks", get(blocks))
+        .route("/blocks/{index}", get(block))
+        .route("/balance/{address}", get(balance))
+        .route("/tx", post(transaction))
+        .route("/mine", post(mine))
+        .route("/metrics", get(metrics))
+        .with_state(chain)
+}
+
+/// Serves the API on `127.0.0.1:port` until the process exits.
+///
+/// # Arguments
+///
+/// * `chain` - Chain to serve
+/// * `port` - TCP port to listen on
+///
+/// # Returns
+///
+/// A [`BlockchainError::Io`] error if the runtime cannot start or the port
+/// cannot be bound
+pub(crate) fn serve(chain: Chain, port: u16) -> Result<(), BlockchainError> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+        println!("Listening on http://{}", listener.local_addr()?);
+        axum::serve(listener, router(Served::new(chain))).await?;
+        Ok::<_, BlockchainError>(())
+    })
+}
+
+/// Locks the shared chain, recovering it if a handler panicked while holding it.
+fn lock(chain: &SharedChain) -> MutexGuard<'_, Chain> {
+    chain.chain.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns every block of the chain.
+async fn blocks(State(chain): State<SharedChain>) -> Json<Vec<Block>> {
+    Json(lock(&chain).get_chain().clone())
+}
+
+/// Returns the block at the given index.
+async fn block(
+    State(chain): State<SharedChain>,
+    Path(index): Path<u64>,
+) -> Result<Json<Block>, ApiError> {
+    lock(&chain)
+        .get_block_by_index(index)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": format!("no block at index {index}") })),
+            )
+        })
+}
+
+/// Returns the confirmed balance of an address.
+async fn balance(
+    State(chain): State<SharedChain>,
+    Path(address): Path<String>,
+) -> Json<BalanceResponse> {
+    let balance = lock(&chain).get_balance(&address);
+    Json(BalanceResponse { address, balance })
+}
+
+/// Adds a pending transaction and echoes it back.
+async fn transaction(
+    State(chain): State<SharedChain>,
+    Json(tx): Json<TxRequest>,
+) -> Result<(StatusCode, Json<TxRequest>), ApiError> {
+    lock(&chain)
+        .add_transaction(tx.sender.clone(), tx.receiver.clone(), tx.amount, None)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": e.to_string() })),
+            )
+        })?;
+    Ok((StatusCode::CREATED, Json(tx)))
+}
+
+/// Mines a block paying the chain's miner and returns it.
+async fn mine(State(chain): State<SharedChain>) -> Result<(StatusCode, Json<Block>), ApiError> {
+    let mined = {
+        let mut chain = lock(&chain);
+        chain
+            .generate_new_block()
+            .map_err(|e| e.to_string())
+            .and_then(|()| {
+                chain
+                    .latest()
+                    .cloned()
+                    .ok_or_else(|| "mined chain has no block".to_string())
+            })
+    };
+    match mined {
+        Ok(block) => Ok((StatusCode::CREATED, Json(block))),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": error })),
+        )),
+    }
+}
+
+/// Returns chain statistics in the Prometheus text exposition format.
+async fn metrics(
+    State(served): State<SharedChain>,
+) -> ([(header::HeaderName, &'static str); 1], String) {
+    let body = render_metrics(&lock(&served), served.start_len);
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+/// Formats the metrics of a chain, one `# HELP`, `# TYPE`, and sample per metric.
+///
+/// # Arguments
+///
+/// * `chain` - Chain to describe
+/// * `start_len` - Number of blocks the chain had when serving started
+fn render_metrics(chain: &Chain, start_len: usize) -> String {
+    let transactions: usize = chain
+        .iter()
+        .map(|block| block.get_transactions().len())
+        .sum();
+    let metrics: [(&str, &str, &str, u64); 7] = [
+        (
+            "blockchain_height",
+            "gauge",
+            "Index of the latest block",
+            chain.height(),
+        ),
+        (
+            "blockchain_transactions_total",
+            "counter",
+            "Transactions in the chain's blocks, coinbases included",
+            transactions as u64,
+        ),
+        (
+            "blockchain_mempool_transactions",
+            "gauge",
+            "Pending transactions waiting for a block",
+            chain.pending_transactions().len() as u64,
+        ),
+        (
+            "blockchain_difficulty",
+            "gauge",
+            "Difficulty the next block is mined at",
+            u64::from(chain.get_difficulty()),
+        ),
+        (
+            "blockchain_total_supply",
+            "gauge",
+            "Coins in existence, in base units",
+            chain.total_supply(),
+        ),
+        (
+            "blockchain_circulating_supply",
+            "gauge",
+            "Coins in existence and not burned, in base units",
+            chain.circulating_supply(),
+        ),
+        (
+            "blockchain_blocks_mined_total",
+            "counter",
+            "Blocks mined since serving started",
+            chain.len().saturating_sub(start_len) as u64,
+        ),
+    ];
+    let mut body = String::new();
+    for (name, kind, help, value) in metrics {
+        writeln!(
+            body,
+            "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}"
+        )
+        .expect("writing to a String cannot fail");
+    }
+    body
+}
@@ -0,0 +1,135 @@
+//! Backends that keep a chain's blocks, so it can be recovered after a crash.
+//!
+//! A chain writes every block it appends to its [`Storage`] before accepting
+//! it, and [`Chain::from_config`](crate::Chain::from_config) resumes from the
+//! stored blocks when there are any. With the `sled` feature, [`SledStorage`]
+//! keeps them in an embedded database on disk.
+use crate::blockchain::Block;
+use crate::error::BlockchainError;
+use std::fmt;
+
+/// A store of blocks keyed by their index.
+pub trait Storage: fmt::Debug + Send + Sync {
+    /// Stores a block under its index, dropping any stored at or above it
+    /// first.
+    fn push(&mut self, block: &Block) -> Result<(), BlockchainError>;
+
+    /// Drops every stored block whose index is `len` or more.
+    fn truncate(&mut self, len: u64) -> Result<(), BlockchainError>;
+
+    /// Reads every stored block in index order.
+    fn load(&self) -> Result<Vec<Block>, BlockchainError>;
+}
+
+/// Keeps blocks only in the chain's own memory, the default.
+///
+/// The chain already holds its blocks, so nothing is stored and a new chain
+/// using this backend always starts from a fresh genesis block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryStorage;
+
+impl Storage for MemoryStorage {
+    fn push(&mut self, _block: &Block) -> Result<(), BlockchainError> {
+        Ok(())
+    }
+
+    fn truncate(&mut self, _len: u64) -> Result<(), BlockchainError> {
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<Block>, BlockchainError> {
+        Ok(Vec::new())
+    }
+}
+
+/// Keeps blocks in a sled database, flushed to disk as each is written.
+///
+/// Blocks are stored in full in the binary format of
+/// [`Chain::save_bin`](crate::Chain::save_bin), keyed by their big-endian
+/// index so they are read back in order.
+#[cfg(feature = "sled")]
+#[derive(Debug)]
+pub struct SledStorage {
+    /// Open database holding the blocks
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledStorage {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open(path: &std::path::Path) -> Result<Self, BlockchainError> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl Storage for SledStorage {
+    fn push(&mut self, block: &Block) -> Result<(), BlockchainError> {
+        self.truncate(block.get_index())?;
+        let bytes = bincode::serde::encode_to_vec(block, bincode::config::standard())?;
+        self.db.insert(block.get_index().to_be_bytes(), bytes)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<(), BlockchainError> {
+        for entry in self.db.range(len.to_be_bytes()..) {
+            let (key, _) = entry?;
+            self.db.remove(key)?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<Block>, BlockchainError> {
+        self.db
+            .iter()
+            .map(|entry| {
+                let (_, bytes) = entry?;
+                let (block, _) =
+                    bincode::serde::decode_from_slice(&bytes, bincode::config::standard())?;
+                Ok(block)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "sled"))]
+mod tests {
+    use super::*;
+    use crate::blockchain::Chain;
+    use crate::config::ChainConfig;
+
+    fn config(path: &std::path::Path) -> ChainConfig {
+        ChainConfig::new("Tilt".to_string()).storage(Box::new(SledStorage::open(path).unwrap()))
+    }
+
+    #[test]
+    fn reopened_sled_storage_recovers_chain() {
+        let path = std::env::temp_dir().join(format!(
+            "blockchain-sandbox-{}-sled-storage",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+
+        let mut chain = Chain::from_config(config(&path)).unwrap();
+        chain.mine_n(3).unwrap();
+        chain.pop_block().unwrap();
+        let blocks = chain.get_chain().clone();
+        drop(chain);
+
+        let mut reopened = Chain::from_config(config(&path)).unwrap();
+        assert_eq!(reopened.get_chain(), blocks.as_slice());
+        assert_eq!(
+            reopened.get_balance("Tilt"),
+            blocks.len() as u64 * crate::config::DEFAULT_REWARD
+        );
+        reopened.generate_new_block().unwrap();
+        drop(reopened);
+
+        assert_eq!(Chain::from_config(config(&path)).unwrap().len(), 4);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+//! 256-bit proof-of-work targets and their compact `nBits` encoding.
+//!
+//! A header hash meets a target when, read as a big-endian 256-bit integer, it
+//! is at or below the target. Unlike leading-zero difficulty, where each step
+//! makes mining 16 times harder, a target can be set to any value in between.
+use crate::encode::Encode;
+use serde::{Deserialize, Serialize};
+
+/// What a leading-zero difficulty counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DifficultyUnit {
+    /// Leading `'0'` hex digits of the hash; each step makes mining 16 times harder
+    #[default]
+    HexDigits,
+    /// Leading zero bits of the raw hash; each step makes mining twice as hard
+    Bits,
+}
+
+impl DifficultyUnit {
+    /// Returns the highest difficulty in this unit, a hash that is all zeros.
+    pub const fn max_difficulty(self) -> u32 {
+        match self {
+            Self::HexDigits => 64,
+            Self::Bits => 256,
+        }
+    }
+
+    /// Returns the number of bits one difficulty step stands for.
+    pub const fn bits_per_step(self) -> u32 {
+        match self {
+            Self::HexDigits => 4,
+            Self::Bits => 1,
+        }
+    }
+
+    /// Returns the target a hash must meet at `difficulty`.
+    ///
+    /// Difficulties above [`DifficultyUnit::max_difficulty`] are treated as the
+    /// maximum.
+    pub fn target(self, difficulty: u32) -> Target {
+        Target::from_leading_zero_bits(
+            difficulty
+                .min(self.max_difficulty())
+                .saturating_mul(self.bits_per_step()),
+        )
+    }
+}
+
+impl Encode for DifficultyUnit {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(match self {
+            Self::HexDigits => 0,
+            Self::Bits => 1,
+        });
+    }
+}
+
+/// A 256-bit proof-of-work target, stored big-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Target([u8; 32]);
+
+impl Target {
+    /// Decodes a target from Bitcoin's compact `nBits` format.
+    ///
+    /// The high byte is the length of the target in bytes and the low three
+    /// bytes its most significant digits, so `0x1d00ffff` is `0xffff` followed
+    /// by 26 zero bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - Compact encoding of the target
+    ///
+    /// # Returns
+    ///
+    /// The target, or None if the encoding has the sign bit set or does not fit
+    /// in 256 bits
+    pub fn from_compact(bits: u32) -> Option<Self> {
+        let size = (bits >> 24) as usize;
+        let mantissa = bits & 0x007f_ffff;
+        if mantissa != 0 && bits & 0x0080_0000 != 0 {
+            return None;
+        }
+        let mut target = [0; 32];
+        for (k, &byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+            // Digits past the last byte are shifted out; past the first they overflow.
+            match (32 + k).checked_sub(size) {
+                Some(position) if position < 32 => target[position] = byte,
+                None if byte != 0 => return None,
+                _ => {}
+            }
+        }
+        Some(Self(target))
+    }
+
+    /// Returns the target met by hashes with at least `zeros` leading zero bits.
+    ///
+    /// Values above 256 are treated as 256, which only the all-zero hash meets.
+    pub fn from_leading_zero_bits(zeros: u32) -> Self {
+        let zeros = zeros.min(256) as usize;
+        let mut target = [0xff; 32];
+        target[..zeros / 8].fill(0);
+        if !zeros.is_multiple_of(8) {
+            target[zeros / 8] = 0xff >> (zeros % 8);
+        }
+        Self(target)
+    }
+
+    /// Returns the target equivalent to a leading-zero hex digit difficulty.
+    ///
+    /// A hash meets it exactly when its first `difficulty` hex digits are
+    /// `'0'`; difficulties above 64 are treated as 64.
+    pub fn from_difficulty(difficulty: u32) -> Self {
+        DifficultyUnit::HexDigits.target(difficulty)
+    }
+
+    /// Checks whether a hash meets the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - Raw 32-byte header digest
+    ///
+    /// # Returns
+    ///
+    /// `true` if the hash, as a big-endian integer, is at most the target
+    pub fn is_met_by(&self, hash: &[u8; 32]) -> bool {
+        hash <= &self.0
+    }
+
+    /// Computes the expected number of hashes needed to meet the target.
+    ///
+    /// # Returns
+    ///
+    /// `2^256 / (target + 1)`, computed from the high 128 bits of the target
+    /// and saturating at `u128::MAX`
+    pub fn work(&self) -> u128 {
+        let mut high = [0; 16];
+        high.copy_from_slice(&self.0[..16]);
+        let high = u128::from_be_bytes(high);
+        if high == 0 {
+            return u128::MAX;
+        }
+        high.checked_add(1)
+            .map_or(1, |divisor| (u128::MAX - high) / divisor + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Target of Bitcoin's genesis block.
+    const GENESIS_BITS: u32 = 0x1d00_ffff;
+
+    /// Adds a signed delta to a big-endian 256-bit integer, wrapping around.
+    fn offset(target: Target, delta: i8) -> [u8; 32] {
+        let mut bytes = target.0;
+        let mut carry = i16::from(delta);
+        for byte in bytes.iter_mut().rev() {
+            let sum = i16::from(*byte) + carry;
+            *byte = sum.rem_euclid(256) as u8;
+            carry = sum.div_euclid(256);
+        }
+        bytes
+    }
+
+    #[test]
+    fn from_compact_places_mantissa_by_size() {
+        let target = Target::from_compact(GENESIS_BITS).unwrap();
+        let mut expected = [0; 32];
+        expected[4] = 0xff;
+        expected[5] = 0xff;
+        assert_eq!(target.0, expected);
+
+        let mut small = [0; 32];
+        small[31] = 0x12;
+        assert_eq!(Target::from_compact(0x0112_3456).unwrap().0, small);
+    }
+
+    #[test]
+    fn from_compact_rejects_negative_and_overflowing_targets() {
+        assert_eq!(Target::from_compact(0x0480_0001), None);
+        assert_eq!(Target::from_compact(0x2201_0000), None);
+        assert!(Target::from_compact(0x2100_ffff).is_some());
+    }
+
+    #[test]
+    fn hash_just_under_target_passes() {
+        let target = Target::from_compact(GENESIS_BITS).unwrap();
+        assert!(target.is_met_by(&offset(target, -1)));
+        assert!(target.is_met_by(&target.0));
+    }
+
+    #[test]
+    fn hash_just_over_target_fails() {
+        let target = Target::from_compact(GENESIS_BITS).unwrap();
+        assert!(!target.is_met_by(&offset(target, 1)));
+    }
+
+    #[test]
+    fn from_difficulty_matches_leading_zero_hex_digits() {
+        let target = Target::from_difficulty(3);
+        let mut hash = [0xff; 32];
+        hash[0] = 0;
+        hash[1] = 0x0f;
+        assert!(target.is_met_by(&hash));
+        hash[1] = 0x10;
+        assert!(!target.is_met_by(&hash));
+        assert_eq!(Target::from_difficulty(2).work(), 256);
+    }
+
+    #[test]
+    fn leading_zero_bits_allow_steps_between_hex_digits() {
+        // 0x04 = 0b0000_0100: five leading zero bits.
+        let mut hash = [0xff; 32];
+        hash[0] = 0x04;
+        assert!(DifficultyUnit::Bits.target(5).is_met_by(&hash));
+        assert!(!DifficultyUnit::Bits.target(6).is_met_by(&hash));
+        assert_eq!(
+            DifficultyUnit::Bits.target(8),
+            DifficultyUnit::HexDigits.target(2)
+        );
+        assert_eq!(DifficultyUnit::Bits.target(5).work(), 32);
+    }
+}
@@ -0,0 +1,115 @@
+//! Wallets holding Ed25519 keypairs used to sign transactions.
+use crate::address;
+use crate::blockchain::{Chain, Transaction};
+use crate::error::BlockchainError;
+use ed25519_dalek::{Signer, SigningKey};
+use rand_core::OsRng;
+
+/// An identity that owns an Ed25519 keypair.
+///
+/// The wallet address is the hex-encoded public key, which is what
+/// [`Transaction::verify`] expects as the sender of a signed transaction.
+#[derive(Debug)]
+pub struct Wallet {
+    /// Private key used to sign transactions
+    signing_key: SigningKey,
+}
+
+impl Wallet {
+    /// Creates a wallet with a freshly generated keypair.
+    pub fn new() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    /// Restores a wallet from a hex-encoded private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - 32-byte private key as a hexadecimal string
+    ///
+    /// # Returns
+    ///
+    /// The wallet, or None if the key is not 32 bytes of valid hex
+    pub fn from_secret_hex(secret: &str) -> Option<Self> {
+        let bytes = <[u8; 32]>::try_from(Chain::hex_to_bytes(secret)?).ok()?;
+        Some(Self {
+            signing_key: SigningKey::from_bytes(&bytes),
+        })
+    }
+
+    /// Returns the wallet address (hex-encoded public key).
+    pub fn address(&self) -> String {
+        Chain::hex_to_string(self.signing_key.verifying_key().as_bytes())
+    }
+
+    /// Returns the wallet's Ed25519 public key, as locked to by
+    /// [`script::pay_to_public_key`](crate::script::pay_to_public_key).
+    pub fn public_key(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    /// Returns the checksummed [`address`](crate::address) of the wallet, which
+    /// can sign transactions just like [`Wallet::address`].
+    pub fn checked_address(&self) -> String {
+        address::from_public_key(self.signing_key.verifying_key().as_bytes())
+    }
+
+    /// Returns the hex-encoded private key.
+    pub fn secret_hex(&self) -> String {
+        Chain::hex_to_string(&self.signing_key.to_bytes())
+    }
+
+    /// Signs a transaction with the wallet's private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Transaction to sign; its sender should be this wallet's address
+    ///
+    /// # Returns
+    ///
+    /// The signature bytes, to be stored in `tx.signature`, or an error if the
+    /// transaction cannot be serialized
+    pub fn sign(&self, tx: &Transaction) -> Result<Vec<u8>, BlockchainError> {
+        Ok(self
+            .signing_key
+            .sign(&tx.signing_payload()?)
+            .to_bytes()
+            .to_vec())
+    }
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallets_have_distinct_addresses() {
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        assert_ne!(alice.address(), bob.address());
+    }
+
+    #[test]
+    fn wallet_signature_verifies() {
+        let wallet = Wallet::new();
+        let mut tx = Transaction::new(wallet.address(), "Bob".to_string(), 1000);
+        tx.signature = Some(wallet.sign(&tx).unwrap());
+        assert!(tx.verify());
+    }
+
+    #[test]
+    fn wallet_restores_from_secret_hex() {
+        let wallet = Wallet::new();
+        let restored = Wallet::from_secret_hex(&wallet.secret_hex()).unwrap();
+        assert_eq!(restored.address(), wallet.address());
+        assert!(Wallet::from_secret_hex("abcd").is_none());
+    }
+}
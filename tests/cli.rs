@@ -0,0 +1,195 @@
+//! Integration tests driving the binary through its subcommands.
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "blockchain-sandbox-cli-{}-{name}",
+        std::process::id()
+    ))
+}
+
+fn run(file: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_blockchain-sandbox"))
+        .args(args)
+        .arg("--file")
+        .arg(file)
+        .args(["--miner", "Tilt", "--difficulty", "1", "--quiet"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn mine_tx_and_balance_run_without_interactive_input() {
+    let file = temp_path("mine.json");
+
+    assert!(run(&file, &["mine"]).status.success());
+    let tx = run(
+        &file,
+        &["tx", "--from", "Tilt", "--to", "Bob", "--amount", "10"],
+    );
+    assert!(tx.status.success());
+    assert!(run(&file, &["mine"]).status.success());
+
+    let balance = run(&file, &["balance", "--address", "Bob"]);
+    assert_eq!(String::from_utf8_lossy(&balance.stdout).trim(), "10");
+    assert!(run(&file, &["validate"]).status.success());
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn tx_without_funds_fails() {
+    let file = temp_path("no-funds.json");
+
+    let tx = run(
+        &file,
+        &["tx", "--from", "Alice", "--to", "Bob", "--amount", "10"],
+    );
+    assert!(!tx.status.success());
+    assert!(String::from_utf8_lossy(&tx.stderr).contains("insufficient funds"));
+
+    std::fs::remove_file(&file).ok();
+}
+
+#[test]
+fn chain_created_with_blake3_validates() {
+    let file = temp_path("blake3.json");
+
+    assert!(
+        run(&file, &["mine", "--hash-algo", "blake3"])
+            .status
+            .success()
+    );
+    assert!(run(&file, &["validate"]).status.success());
+    let saved = std::fs::read_to_string(&file).unwrap();
+    assert!(saved.contains("\"Blake3\""));
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn mine_with_count_mines_that_many_blocks() {
+    let file = temp_path("mine-count.json");
+
+    let mine = run(&file, &["mine", "3"]);
+    assert!(mine.status.success());
+    let stdout = String::from_utf8_lossy(&mine.stdout);
+    assert!(stdout.contains("Mined block #1 "));
+    assert!(stdout.contains("Mined block #3 "));
+    assert!(stdout.contains(" attempts, "));
+    assert_eq!(
+        run(&file, &["balance", "--address", "Tilt"]).stdout,
+        b"200\n"
+    );
+    assert_eq!(
+        run(&file, &["balance", "--address", "Tilt", "--height", "1"]).stdout,
+        b"100\n"
+    );
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn mine_with_json_prints_mined_blocks() {
+    let file = temp_path("mine-json.json");
+
+    let mine = run(&file, &["mine", "2", "--json"]);
+    assert!(mine.status.success());
+    let blocks: serde_json::Value = serde_json::from_slice(&mine.stdout).unwrap();
+    let blocks = blocks.as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0]["index"], 1);
+    assert_eq!(blocks[1]["index"], 2);
+    assert_eq!(blocks[1]["transactions"][0]["receiver"], "Tilt");
+
+    let show = run(&file, &["show", "--json"]);
+    let chain: serde_json::Value = serde_json::from_slice(&show.stdout).unwrap();
+    assert_eq!(chain.as_array().unwrap().len(), 3);
+    assert_eq!(chain[2]["hash"], blocks[1]["hash"]);
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+/// Starts the interactive menu with `args`, answers it with `input`, and waits
+/// for it to exit.
+fn run_interactive(args: &[&str], input: impl AsRef<[u8]>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_blockchain-sandbox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_ref())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn load_starts_menu_on_saved_chain() {
+    let file = temp_path("load.json");
+    assert!(run(&file, &["mine", "2"]).status.success());
+
+    let menu = run_interactive(&["--load", file.to_str().unwrap()], "5\n0\n");
+    assert!(menu.status.success());
+    let stdout = String::from_utf8_lossy(&menu.stdout);
+    assert!(stdout.contains("at height 2"), "{stdout}");
+    assert!(stdout.contains("\n#2 "), "{stdout}");
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn load_of_missing_file_fails() {
+    let menu = run_interactive(&["--load", temp_path("missing.json").to_str().unwrap()], "");
+    assert!(!menu.status.success());
+    assert!(String::from_utf8_lossy(&menu.stderr).contains("Error: "));
+}
+
+#[test]
+fn menu_exits_at_end_of_input_and_skips_blank_choices() {
+    let file = temp_path("eof.json");
+    assert!(run(&file, &["mine"]).status.success());
+    let load = ["--load", file.to_str().unwrap()];
+
+    let menu = run_interactive(&load, "");
+    assert!(menu.status.success());
+    assert!(String::from_utf8_lossy(&menu.stdout).contains("Exiting program."));
+
+    let menu = run_interactive(&load, "\n  \n\t\n");
+    assert!(menu.status.success());
+    let stdout = String::from_utf8_lossy(&menu.stdout);
+    assert_eq!(stdout.matches("Choose an option:").count(), 1, "{stdout}");
+    assert!(!stdout.contains("Invalid choice"), "{stdout}");
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn menu_exits_when_a_prompt_cannot_be_read() {
+    let file = temp_path("unreadable.json");
+    assert!(run(&file, &["mine"]).status.success());
+
+    let menu = run_interactive(&["--load", file.to_str().unwrap()], b"1\n\xff\n");
+    assert!(menu.status.success());
+    let stdout = String::from_utf8_lossy(&menu.stdout);
+    assert!(stdout.contains("Failed to read input"), "{stdout}");
+    assert_eq!(stdout.matches("Choose an option:").count(), 1, "{stdout}");
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn unreadable_chain_settings_fail_instead_of_panicking() {
+    let menu = run_interactive(&[], b"\xff\n");
+    assert!(!menu.status.success());
+    let stderr = String::from_utf8_lossy(&menu.stderr);
+    assert!(stderr.contains("I/O error"), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
@@ -0,0 +1,169 @@
+//! Integration tests driving the REST API started with `--serve`.
+#![cfg(feature = "server")]
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Server process that is killed when dropped.
+struct Server {
+    child: Child,
+    port: u16,
+    file: PathBuf,
+}
+
+impl Server {
+    fn start(name: &str) -> Self {
+        let file = std::env::temp_dir().join(format!(
+            "blockchain-sandbox-server-{}-{name}",
+            std::process::id()
+        ));
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(env!("CARGO_BIN_EXE_blockchain-sandbox"))
+            .arg("--file")
+            .arg(&file)
+            .args(["--miner", "Tilt", "--difficulty", "1"])
+            .args(["--serve", &port.to_string()])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while TcpStream::connect(("127.0.0.1", port)).is_err() {
+            assert!(Instant::now() < deadline, "server did not start");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        Self { child, port, file }
+    }
+
+    /// Sends a request and returns the status code and JSON body.
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = self.request_text(method, path, body);
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    /// Sends a request and returns the status code and raw body.
+    fn request_text(&self, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+        std::fs::remove_file(&self.file).ok();
+    }
+}
+
+#[test]
+fn blocks_endpoints_return_genesis() {
+    let server = Server::start("blocks.json");
+
+    let (status, blocks) = server.request("GET", "/blocks", "");
+    assert_eq!(status, 200);
+    let blocks = blocks.as_array().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert!(blocks[0]["header"].is_object());
+    assert!(blocks[0]["transactions"].is_array());
+
+    let (status, block) = server.request("GET", "/blocks/0", "");
+    assert_eq!(status, 200);
+    assert_eq!(block["index"], 0);
+
+    let (status, error) = server.request("GET", "/blocks/5", "");
+    assert_eq!(status, 404);
+    assert!(error["error"].is_string());
+}
+
+#[test]
+fn balance_endpoint_reports_address_and_balance() {
+    let server = Server::start("balance.json");
+
+    let (status, balance) = server.request("GET", "/balance/Tilt", "");
+    assert_eq!(status, 200);
+    assert_eq!(balance["address"], "Tilt");
+    assert_eq!(balance["balance"], 5_000_000_000_u64);
+}
+
+#[test]
+fn tx_endpoint_accepts_funded_and_rejects_unfunded_transactions() {
+    let server = Server::start("tx.json");
+
+    let (status, tx) = server.request(
+        "POST",
+        "/tx",
+        r#"{"sender":"Tilt","receiver":"Bob","amount":1000000000}"#,
+    );
+    assert_eq!(status, 201);
+    assert_eq!(tx["sender"], "Tilt");
+    assert_eq!(tx["receiver"], "Bob");
+    assert_eq!(tx["amount"], 1_000_000_000_u64);
+
+    let (status, error) = server.request(
+        "POST",
+        "/tx",
+        r#"{"sender":"Alice","receiver":"Bob","amount":1000000000}"#,
+    );
+    assert_eq!(status, 400);
+    assert!(
+        error["error"]
+            .as_str()
+            .unwrap()
+            .contains("insufficient funds")
+    );
+}
+
+#[test]
+fn metrics_endpoint_reports_chain_after_mining() {
+    let server = Server::start("metrics.json");
+
+    for index in 1..=2 {
+        let (status, block) = server.request("POST", "/mine", "");
+        assert_eq!(status, 201);
+        assert_eq!(block["index"], index);
+    }
+
+    let (status, metrics) = server.request_text("GET", "/metrics", "");
+    assert_eq!(status, 200);
+    for name in [
+        "blockchain_height",
+        "blockchain_transactions_total",
+        "blockchain_mempool_transactions",
+        "blockchain_difficulty",
+        "blockchain_total_supply",
+        "blockchain_circulating_supply",
+        "blockchain_blocks_mined_total",
+    ] {
+        assert!(metrics.contains(&format!("# TYPE {name} ")), "{name}");
+    }
+    let lines: Vec<&str> = metrics.lines().collect();
+    assert!(lines.contains(&"blockchain_height 2"), "{metrics}");
+    assert!(
+        lines.contains(&"blockchain_blocks_mined_total 2"),
+        "{metrics}"
+    );
+    assert!(
+        lines.contains(&"blockchain_total_supply 15000000000"),
+        "{metrics}"
+    );
+}